@@ -0,0 +1,72 @@
+use std::{alloc::Layout, mem::MaybeUninit, ptr::NonNull};
+
+use crate::Arena;
+
+/// Implements an `Allocator` trait for `&Arena`.
+///
+/// The standard library's trait (nightly only) and the one from the
+/// allocator-api2 crate are identical apart from their paths, so they share
+/// an implementation.
+macro_rules! impl_allocator {
+    ($allocator:path, $alloc_error:path) => {
+        unsafe impl<A: allocator_api2::alloc::Allocator + Clone> $allocator for &Arena<A> {
+            #[inline]
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, $alloc_error> {
+                match self.try_alloc_raw(layout) {
+                    Ok(ptr) => Ok(NonNull::slice_from_raw_parts(ptr, layout.size())),
+                    Err(_) => Err($alloc_error),
+                }
+            }
+
+            #[inline]
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                // Memory is normally only reclaimed when the arena is
+                // cleared, but the most recent allocation can be given back.
+                self.try_resize_in_place(ptr.as_ptr() as *const MaybeUninit<u8>, layout.size(), 0);
+            }
+
+            #[inline]
+            unsafe fn grow(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<NonNull<[u8]>, $alloc_error> {
+                match self.try_realloc_raw(
+                    ptr.as_ptr() as *mut MaybeUninit<u8>,
+                    old_layout,
+                    new_layout,
+                ) {
+                    Ok(new_ptr) => Ok(NonNull::slice_from_raw_parts(new_ptr, new_layout.size())),
+                    Err(_) => Err($alloc_error),
+                }
+            }
+
+            #[inline]
+            unsafe fn shrink(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<NonNull<[u8]>, $alloc_error> {
+                match self.try_realloc_raw(
+                    ptr.as_ptr() as *mut MaybeUninit<u8>,
+                    old_layout,
+                    new_layout,
+                ) {
+                    Ok(new_ptr) => Ok(NonNull::slice_from_raw_parts(new_ptr, new_layout.size())),
+                    Err(_) => Err($alloc_error),
+                }
+            }
+        }
+    };
+}
+
+#[cfg(feature = "allocator_api")]
+impl_allocator!(std::alloc::Allocator, std::alloc::AllocError);
+
+#[cfg(feature = "allocator-api2")]
+impl_allocator!(
+    allocator_api2::alloc::Allocator,
+    allocator_api2::alloc::AllocError
+);
@@ -0,0 +1,224 @@
+use std::{fmt, marker::PhantomData, ops};
+
+/// A mutable array view with padding between elements.
+///
+/// Returned by `Arena::alloc_array_strided()`.  It works much like a `&mut
+/// [T]`, except that each element is `stride()` bytes apart rather than
+/// `size_of::<T>()` bytes apart.
+pub struct StridedArray<'a, T: Copy> {
+    ptr: *mut T,
+    len: usize,
+    stride: usize,
+    _phantom: PhantomData<&'a mut T>,
+}
+
+impl<'a, T: Copy> StridedArray<'a, T> {
+    /// # Safety
+    ///
+    /// `ptr` must point to `len` initialized elements, each `stride` bytes
+    /// apart, valid and exclusively borrowed for `'a`.
+    pub(crate) unsafe fn from_raw_parts(ptr: *mut T, len: usize, stride: usize) -> Self {
+        StridedArray {
+            ptr,
+            len,
+            stride,
+            _phantom: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Distance in bytes between the start of consecutive elements.
+    #[inline]
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len {
+            Some(unsafe { &*self.element_ptr(index) })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.len {
+            Some(unsafe { &mut *self.element_ptr(index) })
+        } else {
+            None
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        (0..self.len).map(move |i| unsafe { &*self.element_ptr(i) })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        let (ptr, stride) = (self.ptr as *mut u8, self.stride);
+        (0..self.len).map(move |i| unsafe { &mut *(ptr.add(i * stride) as *mut T) })
+    }
+
+    /// Converts the view into an iterator over its elements, with the
+    /// lifetime of the arena.
+    pub fn into_iter_mut(self) -> impl Iterator<Item = &'a mut T> {
+        let (ptr, stride) = (self.ptr as *mut u8, self.stride);
+        (0..self.len).map(move |i| unsafe { &mut *(ptr.add(i * stride) as *mut T) })
+    }
+
+    #[inline(always)]
+    fn element_ptr(&self, index: usize) -> *mut T {
+        unsafe { (self.ptr as *mut u8).add(index * self.stride) as *mut T }
+    }
+}
+
+impl<'a, T: Copy> ops::Index<usize> for StridedArray<'a, T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("Index out of bounds")
+    }
+}
+
+impl<'a, T: Copy> ops::IndexMut<usize> for StridedArray<'a, T> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        self.get_mut(index).expect("Index out of bounds")
+    }
+}
+
+impl<'a, T: Copy + fmt::Debug> fmt::Debug for StridedArray<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// A mutable 2D array view with padding between rows.
+///
+/// Returned by `Arena::alloc_array_2d()`.  Elements are indexed by `(x, y)`,
+/// and each row is `pitch()` bytes apart.  Within a row, elements follow
+/// standard array layout, and each row can be accessed as a plain `[T]`.
+pub struct Array2d<'a, T: Copy> {
+    ptr: *mut T,
+    width: usize,
+    height: usize,
+    pitch: usize,
+    _phantom: PhantomData<&'a mut T>,
+}
+
+impl<'a, T: Copy> Array2d<'a, T> {
+    /// # Safety
+    ///
+    /// `ptr` must point to `height` rows of `width` initialized elements
+    /// each, with rows `pitch` bytes apart, valid and exclusively borrowed for
+    /// `'a`.
+    pub(crate) unsafe fn from_raw_parts(
+        ptr: *mut T,
+        width: usize,
+        height: usize,
+        pitch: usize,
+    ) -> Self {
+        Array2d {
+            ptr,
+            width,
+            height,
+            pitch,
+            _phantom: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Distance in bytes between the start of consecutive rows.
+    #[inline]
+    pub fn pitch(&self) -> usize {
+        self.pitch
+    }
+
+    #[inline]
+    pub fn row(&self, y: usize) -> &[T] {
+        assert!(y < self.height, "Row index out of bounds");
+        unsafe { std::slice::from_raw_parts(self.row_ptr(y), self.width) }
+    }
+
+    #[inline]
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        assert!(y < self.height, "Row index out of bounds");
+        unsafe { std::slice::from_raw_parts_mut(self.row_ptr(y), self.width) }
+    }
+
+    #[inline]
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if y < self.height {
+            self.row(y).get(x)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if y < self.height {
+            self.row_mut(y).get_mut(x)
+        } else {
+            None
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |y| self.row(y))
+    }
+
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [T]> {
+        let (ptr, width, pitch) = (self.ptr as *mut u8, self.width, self.pitch);
+        (0..self.height).map(move |y| unsafe {
+            std::slice::from_raw_parts_mut(ptr.add(y * pitch) as *mut T, width)
+        })
+    }
+
+    #[inline(always)]
+    fn row_ptr(&self, y: usize) -> *mut T {
+        unsafe { (self.ptr as *mut u8).add(y * self.pitch) as *mut T }
+    }
+}
+
+impl<'a, T: Copy> ops::Index<(usize, usize)> for Array2d<'a, T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y).expect("Index out of bounds")
+    }
+}
+
+impl<'a, T: Copy> ops::IndexMut<(usize, usize)> for Array2d<'a, T> {
+    #[inline]
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        self.get_mut(x, y).expect("Index out of bounds")
+    }
+}
+
+impl<'a, T: Copy + fmt::Debug> fmt::Debug for Array2d<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.rows()).finish()
+    }
+}
@@ -0,0 +1,353 @@
+use std::{
+    alloc::{self, Layout},
+    collections::{linked_list, LinkedList},
+    iter,
+    mem::{ManuallyDrop, MaybeUninit},
+    option,
+    ptr::NonNull,
+    sync::Arc,
+};
+
+use allocator_api2::alloc::{Allocator, Global};
+
+use crate::{ArenaAllocError, BlockSource};
+
+/// A single contiguous block of arena memory.
+///
+/// This is essentially a stripped-down `Vec<MaybeUninit<u8>, A>`, but with a
+/// configurable alignment for its base address.  `len` is how much of the
+/// block has been doled out so far.
+pub(crate) struct Block<A: Allocator + Clone = Global> {
+    ptr: NonNull<MaybeUninit<u8>>,
+    capacity: usize,
+    /// The size the block was allocated with, which `capacity` can be
+    /// shrunk below.
+    alloc_size: usize,
+    /// How many bytes before `ptr` the memory actually starts, if it was
+    /// skipped to align the block.
+    offset: usize,
+    align: usize,
+    len: usize,
+    /// What the block gives its memory back to when dropped.
+    owner: Owner<A>,
+}
+
+/// Where a block's memory came from.
+enum Owner<A> {
+    /// Memory that's managed elsewhere, and isn't freed by the block.
+    Nobody,
+    /// The global allocator, via a `Vec<u8>`.
+    Heap,
+    /// The arena's `BlockSource`.
+    Source(Arc<dyn BlockSource>),
+    /// The arena's allocator.
+    Allocator(A),
+}
+
+// Blocks use their memory exclusively, just like a `Vec` does.
+unsafe impl<A: Allocator + Clone + Send> Send for Block<A> {}
+
+impl Block {
+    /// Allocates a new block from the global allocator with the given
+    /// capacity and base alignment.
+    pub fn new(capacity: usize, align: usize) -> Block {
+        let layout = Layout::from_size_align(capacity, align).expect("Invalid block layout");
+        Block::try_new(capacity, align, &Global)
+            .unwrap_or_else(|_| alloc::handle_alloc_error(layout))
+    }
+}
+
+impl<A: Allocator + Clone> Block<A> {
+    /// Allocates a new block from `allocator` with the given capacity and
+    /// base alignment, or returns an error if the memory can't be
+    /// allocated.
+    pub fn try_new(
+        capacity: usize,
+        align: usize,
+        allocator: &A,
+    ) -> Result<Block<A>, ArenaAllocError> {
+        let layout = Layout::from_size_align(capacity, align)
+            .map_err(|_| ArenaAllocError::LayoutOverflow)?;
+
+        let ptr = if capacity == 0 {
+            // Zero-sized allocations aren't allowed, so we just use a
+            // properly aligned dangling pointer.
+            unsafe { NonNull::new_unchecked(align as *mut MaybeUninit<u8>) }
+        } else {
+            allocator
+                .allocate(layout)
+                .map_err(|_| ArenaAllocError::OutOfMemory(layout))?
+                .cast()
+        };
+
+        Ok(Block {
+            ptr,
+            capacity,
+            alloc_size: capacity,
+            offset: 0,
+            align,
+            len: 0,
+            owner: Owner::Allocator(allocator.clone()),
+        })
+    }
+
+    /// Same as `try_new()`, but gets the memory from `source`.  The block's
+    /// capacity may end up bigger than requested, if the source returns
+    /// more memory.
+    pub fn try_new_in(
+        capacity: usize,
+        align: usize,
+        source: &Arc<dyn BlockSource>,
+    ) -> Result<Block<A>, ArenaAllocError> {
+        let layout = Layout::from_size_align(capacity, align)
+            .map_err(|_| ArenaAllocError::LayoutOverflow)?;
+
+        if capacity == 0 {
+            let ptr = unsafe { NonNull::new_unchecked(align as *mut MaybeUninit<u8>) };
+            return Ok(unsafe { Block::from_raw_parts(ptr, 0, align) });
+        }
+        let memory = source.acquire(layout)?;
+        let capacity = memory.len().max(capacity);
+
+        Ok(Block {
+            ptr: memory.cast(),
+            capacity,
+            alloc_size: capacity,
+            offset: 0,
+            align,
+            len: 0,
+            owner: Owner::Source(source.clone()),
+        })
+    }
+
+    /// Creates a block that takes over the memory of `vec`, including its
+    /// unused capacity.  The contents of the `Vec` are discarded.
+    ///
+    /// The start of the memory is skipped up to the first address aligned
+    /// to `align`, so the block may end up with no capacity at all.
+    pub fn from_vec(vec: Vec<u8>, align: usize) -> Block<A> {
+        let mut vec = ManuallyDrop::new(vec);
+        let addr = vec.as_ptr() as usize;
+        let offset = (addr.wrapping_neg() & (align - 1)).min(vec.capacity());
+        let capacity = vec.capacity() - offset;
+        Block {
+            ptr: NonNull::new(unsafe { vec.as_mut_ptr().add(offset) } as *mut MaybeUninit<u8>)
+                .unwrap(),
+            capacity,
+            alloc_size: capacity,
+            offset,
+            // Freeing a block with this alignment matches how `Vec<u8>`
+            // frees its memory.
+            align: 1,
+            len: 0,
+            owner: Owner::Heap,
+        }
+    }
+
+    /// Creates a block from memory that's managed elsewhere.  The memory is
+    /// not freed when the block is dropped.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for reads and writes of `capacity` bytes for as
+    /// long as the block exists, and not be used by anything else during
+    /// that time.
+    pub unsafe fn from_raw_parts(
+        ptr: NonNull<MaybeUninit<u8>>,
+        capacity: usize,
+        align: usize,
+    ) -> Block<A> {
+        Block {
+            ptr,
+            capacity,
+            alloc_size: capacity,
+            offset: 0,
+            align,
+            len: 0,
+            owner: Owner::Nobody,
+        }
+    }
+
+    #[inline(always)]
+    pub fn as_ptr(&self) -> *const MaybeUninit<u8> {
+        self.ptr.as_ptr()
+    }
+
+    #[inline(always)]
+    pub fn as_mut_ptr(&mut self) -> *mut MaybeUninit<u8> {
+        self.ptr.as_ptr()
+    }
+
+    /// The part of the block that has been doled out.
+    #[inline(always)]
+    pub fn as_slice(&self) -> &[MaybeUninit<u8>] {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline(always)]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The amount of memory the block takes up, including any capacity it
+    /// was shrunk by.
+    #[inline(always)]
+    pub fn alloc_size(&self) -> usize {
+        self.offset + self.alloc_size
+    }
+
+    /// # Safety
+    ///
+    /// `new_len` must not exceed the block's capacity.
+    #[inline(always)]
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        debug_assert!(new_len <= self.capacity);
+        self.len = new_len;
+    }
+
+    /// Reduces the block's capacity to its length, so that the rest of it
+    /// is no longer used.
+    #[inline(always)]
+    pub fn shrink_to_len(&mut self) {
+        self.capacity = self.len;
+    }
+
+    /// Permanently reduces the block's capacity to its length, after the
+    /// rest of its memory has been given to something else.  Only valid for
+    /// blocks that don't own their memory.
+    #[inline(always)]
+    pub fn give_up_unused(&mut self) {
+        debug_assert!(matches!(self.owner, Owner::Nobody));
+        self.capacity = self.len;
+        self.alloc_size = self.len;
+    }
+
+    /// Empties the block, restoring any capacity it was shrunk by.
+    #[inline(always)]
+    pub fn reset(&mut self) {
+        self.capacity = self.alloc_size;
+        self.len = 0;
+    }
+}
+
+impl<A: Allocator + Clone> Drop for Block<A> {
+    fn drop(&mut self) {
+        if self.alloc_size() == 0 {
+            return;
+        }
+
+        let layout = unsafe { Layout::from_size_align_unchecked(self.alloc_size(), self.align) };
+        let ptr = unsafe { NonNull::new_unchecked(self.ptr.as_ptr().sub(self.offset) as *mut u8) };
+        match &self.owner {
+            Owner::Nobody => {}
+            Owner::Heap => unsafe { alloc::dealloc(ptr.as_ptr(), layout) },
+            Owner::Source(source) => unsafe { source.release(ptr, layout) },
+            Owner::Allocator(allocator) => unsafe { allocator.deallocate(ptr, layout) },
+        }
+    }
+}
+
+/// An arena's blocks, with the current block at the front.
+///
+/// This is a `LinkedList<Block>`, except that the front block is stored
+/// inline.  That way an arena with only one block, such as a `BufferArena`,
+/// doesn't need any heap memory to keep track of it.
+pub(crate) struct BlockList<A: Allocator + Clone = Global> {
+    front: Option<Block<A>>,
+    /// The rest of the blocks, which is always empty if `front` is `None`.
+    rest: LinkedList<Block<A>>,
+}
+
+impl<A: Allocator + Clone> Default for BlockList<A> {
+    fn default() -> BlockList<A> {
+        BlockList::new()
+    }
+}
+
+impl<A: Allocator + Clone> BlockList<A> {
+    pub fn new() -> BlockList<A> {
+        BlockList {
+            front: None,
+            rest: LinkedList::new(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.front.is_some() as usize + self.rest.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.front.is_none()
+    }
+
+    #[inline(always)]
+    pub fn front(&self) -> Option<&Block<A>> {
+        self.front.as_ref()
+    }
+
+    #[inline(always)]
+    pub fn front_mut(&mut self) -> Option<&mut Block<A>> {
+        self.front.as_mut()
+    }
+
+    pub fn back(&self) -> Option<&Block<A>> {
+        self.rest.back().or(self.front.as_ref())
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut Block<A>> {
+        if self.rest.is_empty() {
+            self.front.as_mut()
+        } else {
+            self.rest.back_mut()
+        }
+    }
+
+    pub fn push_front(&mut self, block: Block<A>) {
+        if let Some(old_front) = self.front.replace(block) {
+            self.rest.push_front(old_front);
+        }
+    }
+
+    pub fn push_back(&mut self, block: Block<A>) {
+        if self.front.is_none() {
+            self.front = Some(block);
+        } else {
+            self.rest.push_back(block);
+        }
+    }
+
+    pub fn pop_front(&mut self) -> Option<Block<A>> {
+        let front = self.front.take();
+        self.front = self.rest.pop_front();
+        front
+    }
+
+    pub fn pop_back(&mut self) -> Option<Block<A>> {
+        self.rest.pop_back().or_else(|| self.front.take())
+    }
+
+    pub fn clear(&mut self) {
+        self.front = None;
+        self.rest.clear();
+    }
+
+    pub fn iter(&self) -> iter::Chain<option::Iter<'_, Block<A>>, linked_list::Iter<'_, Block<A>>> {
+        self.front.iter().chain(self.rest.iter())
+    }
+}
+
+impl<A: Allocator + Clone> IntoIterator for BlockList<A> {
+    type Item = Block<A>;
+    type IntoIter = iter::Chain<option::IntoIter<Block<A>>, linked_list::IntoIter<Block<A>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.front.into_iter().chain(self.rest)
+    }
+}
@@ -0,0 +1,225 @@
+use std::{borrow, cmp::Ordering, fmt, hash, marker::PhantomData, ops, ptr::NonNull};
+
+use allocator_api2::alloc::Allocator;
+
+use crate::Arena;
+
+/// An owning pointer to a value stored in an arena.
+///
+/// Unlike a plain `&mut T` from the arena, an `ABox` runs `T`'s destructor
+/// when it's dropped.  The memory itself is still only reclaimed when the
+/// arena is cleared or dropped.
+///
+/// ```rust
+/// # use kioku::{ABox, Arena};
+/// let arena = Arena::new();
+/// let mut names = ABox::new_in(vec!["Alice".to_string()], &arena);
+/// names.push("Bob".into());
+/// assert_eq!(2, names.len());
+///
+/// // The `Vec` and its strings are freed here.
+/// drop(names);
+/// ```
+///
+/// Boxes can be converted to trait objects (or slices) with `abox_unsize!`,
+/// since custom unsizing coercions aren't possible on stable Rust.
+pub struct ABox<'a, T: ?Sized> {
+    ptr: NonNull<T>,
+    _phantom: PhantomData<(&'a mut T, T)>,
+}
+
+// Same as `&'a mut T`.
+unsafe impl<'a, T: ?Sized + Send> Send for ABox<'a, T> {}
+unsafe impl<'a, T: ?Sized + Sync> Sync for ABox<'a, T> {}
+
+impl<'a, T> ABox<'a, T> {
+    /// Moves `value` into the arena, and returns a box owning it.
+    #[inline]
+    pub fn new_in<A: Allocator + Clone>(value: T, arena: &'a Arena<A>) -> ABox<'a, T> {
+        let memory = arena.alloc_uninit::<T>();
+        unsafe {
+            memory.as_mut_ptr().write(value);
+            ABox::from_raw(memory.as_mut_ptr())
+        }
+    }
+
+    /// Moves the value back out of the box.
+    #[inline]
+    pub fn into_inner(b: ABox<'a, T>) -> T {
+        let value = unsafe { b.ptr.as_ptr().read() };
+        std::mem::forget(b);
+        value
+    }
+}
+
+impl<'a, T: ?Sized> ABox<'a, T> {
+    /// Creates a box from a raw pointer to a value in an arena.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to a valid, initialized `T` that's valid for `'a`, and
+    /// nothing else may access or drop it afterwards.
+    #[inline]
+    pub unsafe fn from_raw(ptr: *mut T) -> ABox<'a, T> {
+        ABox {
+            ptr: NonNull::new_unchecked(ptr),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Consumes the box, returning a raw pointer to its value.
+    ///
+    /// The value is no longer dropped by anything, so it's up to the caller
+    /// to do so if needed, for example with `from_raw()`.
+    #[inline]
+    pub fn into_raw(b: ABox<'a, T>) -> *mut T {
+        let ptr = b.ptr.as_ptr();
+        std::mem::forget(b);
+        ptr
+    }
+
+    /// Consumes the box, returning a reference to its value with the lifetime
+    /// of the arena.
+    ///
+    /// The value is never dropped, just like any other arena allocation.
+    #[inline]
+    pub fn leak(b: ABox<'a, T>) -> &'a mut T {
+        unsafe { &mut *ABox::into_raw(b) }
+    }
+
+    // Used by `abox_unsize!` to make sure the lifetime is carried over.
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn __lifetime(_: &ABox<'a, T>) -> PhantomData<&'a ()> {
+        PhantomData
+    }
+
+    #[doc(hidden)]
+    #[inline(always)]
+    pub unsafe fn __from_raw_with_lifetime(ptr: *mut T, _: PhantomData<&'a ()>) -> ABox<'a, T> {
+        ABox::from_raw(ptr)
+    }
+}
+
+/// Converts an `ABox<T>` into an `ABox<U>`, where `T` unsizes to `U`.
+///
+/// This is typically used to make trait objects, such as `ABox<dyn Trait>`,
+/// which still drop the concrete value properly.  The target type is
+/// inferred, so it usually needs a type annotation.
+///
+/// (Plain arena references don't need this: a `&mut T` from the arena
+/// already coerces to `&mut dyn Trait` on its own.)
+///
+/// ```rust
+/// # use kioku::{abox_unsize, ABox, Arena};
+/// use std::fmt::Display;
+///
+/// let arena = Arena::new();
+/// let items: Vec<ABox<dyn Display>> = vec![
+///     abox_unsize!(ABox::new_in(42, &arena)),
+///     abox_unsize!(ABox::new_in(String::from("Hello"), &arena)),
+/// ];
+/// assert_eq!("42 Hello", format!("{} {}", items[0], items[1]));
+/// ```
+#[macro_export]
+macro_rules! abox_unsize {
+    ($abox:expr) => {{
+        let abox = $abox;
+        let lifetime = $crate::ABox::__lifetime(&abox);
+        let ptr = $crate::ABox::into_raw(abox);
+        // Safe because the pointer came straight from a box with the same
+        // lifetime, and unsizing doesn't change what it points to.
+        unsafe { $crate::ABox::__from_raw_with_lifetime(ptr, lifetime) }
+    }};
+}
+
+impl<'a, T: ?Sized> Drop for ABox<'a, T> {
+    fn drop(&mut self) {
+        unsafe { std::ptr::drop_in_place(self.ptr.as_ptr()) }
+    }
+}
+
+impl<'a, T: ?Sized> ops::Deref for ABox<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<'a, T: ?Sized> ops::DerefMut for ABox<'a, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<'a, T: ?Sized> AsRef<T> for ABox<'a, T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T: ?Sized> AsMut<T> for ABox<'a, T> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<'a, T: ?Sized> borrow::Borrow<T> for ABox<'a, T> {
+    #[inline]
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T: ?Sized> borrow::BorrowMut<T> for ABox<'a, T> {
+    #[inline]
+    fn borrow_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<'a, 'b, T: ?Sized + PartialEq> PartialEq<ABox<'b, T>> for ABox<'a, T> {
+    #[inline]
+    fn eq(&self, other: &ABox<'b, T>) -> bool {
+        **self == **other
+    }
+}
+
+impl<'a, T: ?Sized + Eq> Eq for ABox<'a, T> {}
+
+impl<'a, 'b, T: ?Sized + PartialOrd> PartialOrd<ABox<'b, T>> for ABox<'a, T> {
+    #[inline]
+    fn partial_cmp(&self, other: &ABox<'b, T>) -> Option<Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<'a, T: ?Sized + Ord> Ord for ABox<'a, T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<'a, T: ?Sized + hash::Hash> hash::Hash for ABox<'a, T> {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl<'a, T: ?Sized + fmt::Debug> fmt::Debug for ABox<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<'a, T: ?Sized + fmt::Display> fmt::Display for ABox<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
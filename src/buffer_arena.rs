@@ -0,0 +1,115 @@
+use std::{fmt, marker::PhantomData, mem::MaybeUninit, ops, ptr::NonNull};
+
+use crate::{block::Block, Arena, RetentionPolicy};
+
+/// An arena that allocates exclusively from a borrowed buffer.
+///
+/// Created with `BufferArena::new()`.  This derefs to `Arena`, so it has
+/// the full allocation API, but it never allocates blocks of its own: once
+/// the buffer is used up, the `try_*` methods return an error and the
+/// others panic.  This makes it suitable for places where the global
+/// allocator can't be used, such as with stack buffers, or in embedded and
+/// real-time code.
+///
+/// ```rust
+/// # use kioku::BufferArena;
+/// use std::mem::MaybeUninit;
+///
+/// let mut buffer = [MaybeUninit::uninit(); 256];
+/// let mut arena = BufferArena::new(&mut buffer);
+/// let numbers = arena.alloc_array(7u32, 32);
+/// assert_eq!(7 * 32, numbers.iter().sum::<u32>());
+/// assert!(arena.try_alloc_array(0u8, 200).is_err());
+///
+/// arena.clear();
+/// assert!(arena.try_alloc_array(0u8, 200).is_ok());
+/// ```
+///
+/// The buffer is tracked without any heap allocation, so the arena can be
+/// created, allocated from, and cleared with no global allocator at all.
+/// Features that need more bookkeeping, such as `alloc_handle()` and
+/// `region()`, still use the global allocator, and regions can't allocate
+/// at all, since they need blocks of their own.  Sub-arenas work, and
+/// allocate from the buffer.
+pub struct BufferArena<'buf> {
+    arena: Arena,
+    buffer: NonNull<MaybeUninit<u8>>,
+    buffer_len: usize,
+    _phantom: PhantomData<&'buf mut [MaybeUninit<u8>]>,
+}
+
+// The buffer is borrowed mutably, so it can go wherever the arena does.
+unsafe impl<'buf> Send for BufferArena<'buf> {}
+
+impl<'buf> BufferArena<'buf> {
+    /// Creates an arena that allocates from `buffer`.
+    pub fn new(buffer: &'buf mut [MaybeUninit<u8>]) -> BufferArena<'buf> {
+        // The buffer takes up the whole memory limit, so any attempt to
+        // allocate a new block fails.  It may not be aligned, but the arena
+        // aligns each allocation itself.
+        let arena = Arena::new()
+            .with_block_size(buffer.len().max(1))
+            .with_block_alignment(1)
+            .with_retention_policy(RetentionPolicy::Everything)
+            .with_memory_limit(0);
+
+        let arena = BufferArena {
+            arena,
+            buffer: NonNull::new(buffer.as_mut_ptr()).unwrap(),
+            buffer_len: buffer.len(),
+            _phantom: PhantomData,
+        };
+        arena.install_buffer();
+        arena
+    }
+
+    /// The number of bytes left in the buffer, not counting any padding
+    /// needed for alignment.
+    pub fn remaining(&self) -> usize {
+        let blocks = self.arena.blocks.borrow();
+        let spare_blocks = self.arena.spare_blocks.borrow();
+        match blocks.front().or_else(|| spare_blocks.first()) {
+            Some(block) => block.capacity() - block.len(),
+            None => 0,
+        }
+    }
+
+    /// Frees all memory allocated from the arena, so that the whole buffer
+    /// can be used again.
+    pub fn clear(&mut self) {
+        // Safe because we have a mutable reference.  Nothing is retained, so
+        // the buffer's block is dropped (which doesn't free it) rather than
+        // moved to the spare blocks, and then a fresh one is put in place.
+        unsafe { self.arena.clear_unchecked_with(RetentionPolicy::Nothing) };
+        self.install_buffer();
+    }
+
+    /// Makes the whole buffer the arena's current block.
+    fn install_buffer(&self) {
+        // An empty buffer can't be allocated from anyway.
+        if self.buffer_len == 0 {
+            return;
+        }
+
+        // Safe because the buffer is borrowed for as long as the arena
+        // exists, and the arena has no other block using it.
+        let block = unsafe { Block::from_raw_parts(self.buffer, self.buffer_len, 1) };
+        self.arena.stat_space_occupied.set(block.capacity());
+        self.arena.blocks.borrow_mut().push_front(block);
+    }
+}
+
+impl<'buf> ops::Deref for BufferArena<'buf> {
+    type Target = Arena;
+
+    #[inline]
+    fn deref(&self) -> &Arena {
+        &self.arena
+    }
+}
+
+impl<'buf> fmt::Debug for BufferArena<'buf> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("BufferArena").field(&self.arena).finish()
+    }
+}
@@ -0,0 +1,81 @@
+use allocator_api2::alloc::{Allocator, Global};
+
+use super::{String, Vec, VecDeque};
+use crate::Arena;
+
+/// Like `FromIterator`, but for collections stored in an arena.
+pub trait FromIteratorIn<'a, T, A: Allocator + Clone = Global>: Sized {
+    fn from_iter_in<I: IntoIterator<Item = T>>(iter: I, arena: &'a Arena<A>) -> Self;
+}
+
+/// Extension methods for collecting iterators into an arena.
+///
+/// This is implemented for all iterators.
+///
+/// ```rust
+/// # use kioku::Arena;
+/// use kioku::collections::{CollectIn, Vec};
+///
+/// let arena = Arena::new();
+/// let squares: Vec<u32> = (1..=4).map(|n| n * n).collect_in(&arena);
+/// assert_eq!(&[1, 4, 9, 16], squares.into_slice());
+///
+/// let text: &mut str = "hello".chars().rev().collect_str_in(&arena);
+/// assert_eq!("olleh", text);
+/// ```
+pub trait CollectIn: Iterator + Sized {
+    /// Collects the iterator into an arena-backed collection.
+    #[inline]
+    fn collect_in<'a, A, C>(self, arena: &'a Arena<A>) -> C
+    where
+        A: Allocator + Clone,
+        C: FromIteratorIn<'a, Self::Item, A>,
+    {
+        C::from_iter_in(self, arena)
+    }
+
+    /// Collects the iterator into a `str` with the lifetime of the arena.
+    ///
+    /// Same as `Arena::collect_str()`.
+    #[inline]
+    fn collect_str_in<A: Allocator + Clone>(self, arena: &Arena<A>) -> &mut str
+    where
+        Self: Iterator<Item = char>,
+    {
+        arena.collect_str(self)
+    }
+}
+
+impl<I: Iterator> CollectIn for I {}
+
+impl<'a, T, A: Allocator + Clone> FromIteratorIn<'a, T, A> for Vec<'a, T, A> {
+    fn from_iter_in<I: IntoIterator<Item = T>>(iter: I, arena: &'a Arena<A>) -> Self {
+        let mut vec = Vec::new_in(arena);
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> FromIteratorIn<'a, T, A> for VecDeque<'a, T, A> {
+    fn from_iter_in<I: IntoIterator<Item = T>>(iter: I, arena: &'a Arena<A>) -> Self {
+        let mut queue = VecDeque::new_in(arena);
+        queue.extend(iter);
+        queue
+    }
+}
+
+impl<'a, A: Allocator + Clone> FromIteratorIn<'a, char, A> for String<'a, A> {
+    fn from_iter_in<I: IntoIterator<Item = char>>(iter: I, arena: &'a Arena<A>) -> Self {
+        let mut string = String::new_in(arena);
+        string.extend(iter);
+        string
+    }
+}
+
+impl<'a, 'b, A: Allocator + Clone> FromIteratorIn<'a, &'b str, A> for String<'a, A> {
+    fn from_iter_in<I: IntoIterator<Item = &'b str>>(iter: I, arena: &'a Arena<A>) -> Self {
+        let mut string = String::new_in(arena);
+        string.extend(iter);
+        string
+    }
+}
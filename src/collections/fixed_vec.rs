@@ -0,0 +1,149 @@
+use std::{fmt, mem::MaybeUninit, ops, slice};
+
+use allocator_api2::alloc::{Allocator, Global};
+
+use crate::Arena;
+
+/// A vector with a fixed capacity, stored in an arena.
+///
+/// The full capacity is allocated up-front, and elements can then be pushed
+/// until it's full.  This is useful when an upper bound on the number of
+/// elements is known ahead of time, but not the exact number.  It can be
+/// finished into a plain `&mut [T]` with the lifetime of the arena via
+/// `into_slice()`.
+///
+/// ```rust
+/// # use kioku::{Arena, collections::FixedVec};
+/// let arena = Arena::new();
+/// let mut vec = FixedVec::with_capacity_in(4, &arena);
+/// vec.push(1);
+/// vec.push(2);
+/// assert_eq!(Ok(()), vec.try_push(3));
+/// assert_eq!(Ok(()), vec.try_push(4));
+/// assert_eq!(Err(5), vec.try_push(5));
+///
+/// let array: &mut [i32] = vec.into_slice();
+/// assert_eq!(&[1, 2, 3, 4], array);
+/// ```
+pub struct FixedVec<'a, T: Copy, A: Allocator + Clone = Global> {
+    arena: &'a Arena<A>,
+    memory: &'a mut [MaybeUninit<T>],
+    len: usize,
+}
+
+impl<'a, T: Copy, A: Allocator + Clone> FixedVec<'a, T, A> {
+    /// Creates a new empty vector with room for exactly `capacity` elements.
+    pub fn with_capacity_in(capacity: usize, arena: &'a Arena<A>) -> FixedVec<'a, T, A> {
+        FixedVec {
+            arena,
+            memory: arena.alloc_array_uninit(capacity),
+            len: 0,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.memory.len()
+    }
+
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len == self.memory.len()
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.memory.as_ptr() as *const T, self.len) }
+    }
+
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.memory.as_mut_ptr() as *mut T, self.len) }
+    }
+
+    /// Appends `value` to the end of the vector.
+    ///
+    /// Panics if the vector is already full.
+    #[inline]
+    pub fn push(&mut self, value: T) {
+        assert!(!self.is_full(), "FixedVec is already full");
+        self.memory[self.len] = MaybeUninit::new(value);
+        self.len += 1;
+    }
+
+    /// Appends `value` to the end of the vector if there's room, and
+    /// otherwise returns it as an error.
+    #[inline]
+    pub fn try_push(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+        self.push(value);
+        Ok(())
+    }
+
+    /// Removes and returns the last element, if any.
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(unsafe { self.memory[self.len].as_ptr().read() })
+    }
+
+    /// Empties the vector, keeping its capacity.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Finishes the vector, returning its elements as a `[T]` with the
+    /// lifetime of the arena.
+    pub fn into_slice(self) -> &'a mut [T] {
+        let ptr = self.memory.as_mut_ptr();
+        let capacity = self.memory.len();
+
+        // Give any unused capacity back to the arena if we can.
+        if capacity > 0 {
+            self.arena.try_resize_in_place(
+                ptr as *const MaybeUninit<u8>,
+                capacity * std::mem::size_of::<T>(),
+                self.len * std::mem::size_of::<T>(),
+            );
+        }
+
+        unsafe { slice::from_raw_parts_mut(ptr as *mut T, self.len) }
+    }
+}
+
+impl<'a, T: Copy, A: Allocator + Clone> ops::Deref for FixedVec<'a, T, A> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<'a, T: Copy, A: Allocator + Clone> ops::DerefMut for FixedVec<'a, T, A> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<'a, T: Copy + fmt::Debug, A: Allocator + Clone> fmt::Debug for FixedVec<'a, T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
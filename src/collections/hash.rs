@@ -0,0 +1,33 @@
+use allocator_api2::alloc::Global;
+
+use crate::Arena;
+
+/// A hashbrown `HashMap` with its table stored in an arena.
+///
+/// This is just a type alias, so all of hashbrown's `*_in()` constructors work
+/// with it:
+///
+/// ```rust
+/// # use kioku::{Arena, collections::HashMap};
+/// let arena = Arena::new();
+/// let mut symbols = HashMap::new_in(&arena);
+/// symbols.insert(arena.copy_str("foo"), 1);
+/// symbols.insert(arena.copy_str("bar"), 2);
+/// assert_eq!(Some(&2), symbols.get("bar"));
+/// ```
+///
+/// When the table grows, the old table is only reclaimed if it was the most
+/// recent allocation in the arena, so reserving enough capacity up-front
+/// with `with_capacity_in()` avoids wasting space.
+///
+/// Only available with the `hashbrown` feature.
+pub type HashMap<'a, K, V, S = hashbrown::DefaultHashBuilder, A = Global> =
+    hashbrown::HashMap<K, V, S, &'a Arena<A>>;
+
+/// A hashbrown `HashSet` with its table stored in an arena.
+///
+/// See `HashMap` for details.
+///
+/// Only available with the `hashbrown` feature.
+pub type HashSet<'a, T, S = hashbrown::DefaultHashBuilder, A = Global> =
+    hashbrown::HashSet<T, S, &'a Arena<A>>;
@@ -0,0 +1,26 @@
+//! Collection types backed by arena memory.
+//!
+//! The growable collections grow by reallocating within the arena.  When a
+//! collection's buffer is the most recent allocation in the arena it is grown
+//! in place, and otherwise a new buffer is allocated and the contents copied
+//! over.  The old buffer is not reclaimed until the arena is cleared or
+//! dropped.
+
+mod collect;
+mod fixed_vec;
+#[cfg(feature = "hashbrown")]
+mod hash;
+mod string;
+mod vec;
+mod vec_deque;
+
+pub use self::{
+    collect::{CollectIn, FromIteratorIn},
+    fixed_vec::FixedVec,
+    string::String,
+    vec::Vec,
+    vec_deque::VecDeque,
+};
+
+#[cfg(feature = "hashbrown")]
+pub use self::hash::{HashMap, HashSet};
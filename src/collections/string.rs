@@ -0,0 +1,172 @@
+use std::{fmt, ops, str};
+
+use allocator_api2::alloc::{Allocator, Global};
+
+use super::Vec;
+use crate::Arena;
+
+/// A growable UTF-8 string stored in an arena.
+///
+/// This works much like `std::string::String`, and can be finished into a
+/// plain `&mut str` with the lifetime of the arena via `into_str()`.
+///
+/// ```rust
+/// # use kioku::{Arena, collections::String};
+/// use std::fmt::Write;
+///
+/// let arena = Arena::new();
+/// let mut text = String::new_in(&arena);
+/// text.push_str("Hello");
+/// write!(text, " {}!", "there").unwrap();
+///
+/// let text: &mut str = text.into_str();
+/// assert_eq!("Hello there!", text);
+/// ```
+pub struct String<'a, A: Allocator + Clone = Global> {
+    vec: Vec<'a, u8, A>,
+}
+
+impl<'a, A: Allocator + Clone> String<'a, A> {
+    /// Creates a new empty string.
+    ///
+    /// Doesn't allocate anything until something is added to it.
+    pub fn new_in(arena: &'a Arena<A>) -> String<'a, A> {
+        String {
+            vec: Vec::new_in(arena),
+        }
+    }
+
+    /// Creates a new empty string with room for at least `capacity` bytes.
+    pub fn with_capacity_in(capacity: usize, arena: &'a Arena<A>) -> String<'a, A> {
+        String {
+            vec: Vec::with_capacity_in(capacity, arena),
+        }
+    }
+
+    /// Creates a new string initialized to the contents of `text`.
+    pub fn from_str_in(text: &str, arena: &'a Arena<A>) -> String<'a, A> {
+        let mut string = String::with_capacity_in(text.len(), arena);
+        string.push_str(text);
+        string
+    }
+
+    /// Length of the string in bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// The number of bytes the string can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.vec.capacity()
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        unsafe { str::from_utf8_unchecked(self.vec.as_slice()) }
+    }
+
+    #[inline]
+    pub fn as_mut_str(&mut self) -> &mut str {
+        unsafe { str::from_utf8_unchecked_mut(self.vec.as_mut_slice()) }
+    }
+
+    /// Appends `text` to the end of the string.
+    pub fn push_str(&mut self, text: &str) {
+        self.vec.extend_from_slice_copy(text.as_bytes());
+    }
+
+    /// Appends `c` to the end of the string.
+    pub fn push(&mut self, c: char) {
+        self.push_str(c.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Empties the string, keeping its capacity.
+    pub fn clear(&mut self) {
+        self.vec.clear();
+    }
+
+    /// Ensures there is room for at least `additional` more bytes without
+    /// reallocating.
+    pub fn reserve(&mut self, additional: usize) {
+        self.vec.reserve(additional);
+    }
+
+    /// Finishes the string, returning it as a `str` with the lifetime of the
+    /// arena.
+    pub fn into_str(self) -> &'a mut str {
+        unsafe { str::from_utf8_unchecked_mut(self.vec.into_slice()) }
+    }
+}
+
+impl<'a, A: Allocator + Clone> ops::Deref for String<'a, A> {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<'a, A: Allocator + Clone> ops::DerefMut for String<'a, A> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut str {
+        self.as_mut_str()
+    }
+}
+
+impl<'a, A: Allocator + Clone> Extend<char> for String<'a, A> {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for c in iter {
+            self.push(c);
+        }
+    }
+}
+
+impl<'a, 'b, A: Allocator + Clone> Extend<&'b str> for String<'a, A> {
+    fn extend<I: IntoIterator<Item = &'b str>>(&mut self, iter: I) {
+        for text in iter {
+            self.push_str(text);
+        }
+    }
+}
+
+impl<'a, A: Allocator + Clone> fmt::Write for String<'a, A> {
+    #[inline]
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        self.push_str(text);
+        Ok(())
+    }
+
+    #[inline]
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.push(c);
+        Ok(())
+    }
+}
+
+impl<'a, A: Allocator + Clone> fmt::Display for String<'a, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<'a, A: Allocator + Clone> fmt::Debug for String<'a, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<'a, 'b, A: Allocator + Clone> PartialEq<&'b str> for String<'a, A> {
+    fn eq(&self, other: &&'b str) -> bool {
+        self.as_str() == *other
+    }
+}
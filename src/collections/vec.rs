@@ -0,0 +1,351 @@
+use std::{
+    fmt,
+    mem::{self, size_of, MaybeUninit},
+    ops, ptr, slice,
+};
+
+use allocator_api2::alloc::{Allocator, Global};
+
+use crate::Arena;
+
+/// A growable vector stored in an arena.
+///
+/// This works much like `std::vec::Vec`, and can be finished into a plain
+/// `&mut [T]` with the lifetime of the arena via `into_slice()`.
+///
+/// ```rust
+/// # use kioku::{Arena, collections::Vec};
+/// let arena = Arena::new();
+/// let mut vec = Vec::new_in(&arena);
+/// vec.push(1);
+/// vec.extend_from_slice(&[2, 3]);
+/// vec.extend(4..=5);
+///
+/// let array: &mut [i32] = vec.into_slice();
+/// assert_eq!(&[1, 2, 3, 4, 5], array);
+/// ```
+///
+/// Unlike most things in the arena, the elements of a `Vec` are dropped when
+/// the `Vec` itself is dropped.  However, once finished with `into_slice()`
+/// they're never dropped, just like any other arena allocation.
+pub struct Vec<'a, T, A: Allocator + Clone = Global> {
+    arena: &'a Arena<A>,
+    ptr: *mut T,
+    len: usize,
+    capacity: usize,
+}
+
+impl<'a, T, A: Allocator + Clone> Vec<'a, T, A> {
+    /// Creates a new empty vector.
+    ///
+    /// Doesn't allocate anything until something is added to it.
+    pub fn new_in(arena: &'a Arena<A>) -> Vec<'a, T, A> {
+        assert!(
+            size_of::<T>() > 0,
+            "`Arena` does not support zero-sized types."
+        );
+
+        Vec {
+            arena,
+            ptr: ptr::NonNull::dangling().as_ptr(),
+            len: 0,
+            capacity: 0,
+        }
+    }
+
+    /// Creates a new empty vector with room for at least `capacity` elements.
+    pub fn with_capacity_in(capacity: usize, arena: &'a Arena<A>) -> Vec<'a, T, A> {
+        let mut vec = Vec::new_in(arena);
+        vec.reserve(capacity);
+        vec
+    }
+
+    /// Creates a new vector initialized to clones of the contents of `slice`.
+    pub fn from_slice_in(slice: &[T], arena: &'a Arena<A>) -> Vec<'a, T, A>
+    where
+        T: Clone,
+    {
+        let mut vec = Vec::with_capacity_in(slice.len(), arena);
+        vec.extend_from_slice(slice);
+        vec
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of elements the vector can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+
+    /// The unused capacity at the end of the vector.
+    #[inline]
+    pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        unsafe {
+            slice::from_raw_parts_mut(
+                self.ptr.add(self.len) as *mut MaybeUninit<T>,
+                self.capacity - self.len,
+            )
+        }
+    }
+
+    /// Sets the length of the vector.
+    ///
+    /// # Safety
+    ///
+    /// `new_len` must not exceed the capacity, and any elements up to
+    /// `new_len` must be initialized.
+    #[inline]
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        debug_assert!(new_len <= self.capacity);
+        self.len = new_len;
+    }
+
+    /// Appends `value` to the end of the vector.
+    #[inline]
+    pub fn push(&mut self, value: T) {
+        if self.len == self.capacity {
+            self.reserve(1);
+        }
+        unsafe { self.ptr.add(self.len).write(value) };
+        self.len += 1;
+    }
+
+    /// Removes and returns the last element, if any.
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(unsafe { self.ptr.add(self.len).read() })
+    }
+
+    /// Inserts `value` at `index`, shifting everything after it to the right.
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len, "Insertion index out of bounds");
+        if self.len == self.capacity {
+            self.reserve(1);
+        }
+        unsafe {
+            let p = self.ptr.add(index);
+            ptr::copy(p, p.add(1), self.len - index);
+            p.write(value);
+        }
+        self.len += 1;
+    }
+
+    /// Removes and returns the element at `index`, shifting everything after
+    /// it to the left.
+    ///
+    /// Panics if `index >= len`.
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "Removal index out of bounds");
+        unsafe {
+            let p = self.ptr.add(index);
+            let value = p.read();
+            ptr::copy(p.add(1), p, self.len - index - 1);
+            self.len -= 1;
+            value
+        }
+    }
+
+    /// Shortens the vector to `len` elements, dropping the rest.
+    ///
+    /// Does nothing if the vector is already shorter than that.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        let tail = ptr::slice_from_raw_parts_mut(unsafe { self.ptr.add(len) }, self.len - len);
+        self.len = len;
+        unsafe { ptr::drop_in_place(tail) };
+    }
+
+    /// Empties the vector, keeping its capacity.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Appends clones of the contents of `slice` to the end of the vector.
+    pub fn extend_from_slice(&mut self, slice: &[T])
+    where
+        T: Clone,
+    {
+        self.reserve(slice.len());
+        for item in slice {
+            // Pushed one at a time, so that the length stays correct if
+            // `clone()` panics.
+            unsafe { self.ptr.add(self.len).write(item.clone()) };
+            self.len += 1;
+        }
+    }
+
+    /// Same as `extend_from_slice()`, but faster for `Copy` types.
+    pub fn extend_from_slice_copy(&mut self, slice: &[T])
+    where
+        T: Copy,
+    {
+        self.reserve(slice.len());
+        unsafe {
+            ptr::copy_nonoverlapping(slice.as_ptr(), self.ptr.add(self.len), slice.len());
+        }
+        self.len += slice.len();
+    }
+
+    /// Ensures there is room for at least `additional` more elements without
+    /// reallocating.
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = self
+            .len
+            .checked_add(additional)
+            .expect("Vec capacity overflow");
+        if needed <= self.capacity {
+            return;
+        }
+
+        let min_capacity = match size_of::<T>() {
+            1 => 8,
+            n if n <= 1024 => 4,
+            _ => 1,
+        };
+        let new_capacity = needed.max(self.capacity * 2).max(min_capacity);
+
+        // Try to grow in place first, and otherwise move to a new buffer.
+        if self.capacity > 0 {
+            if let (Some(old_size), Some(new_size)) = (
+                self.capacity.checked_mul(size_of::<T>()),
+                new_capacity.checked_mul(size_of::<T>()),
+            ) {
+                if self.arena.try_resize_in_place(
+                    self.ptr as *const MaybeUninit<u8>,
+                    old_size,
+                    new_size,
+                ) {
+                    self.capacity = new_capacity;
+                    return;
+                }
+            }
+        }
+
+        let memory = self.arena.alloc_array_uninit::<T>(new_capacity);
+        unsafe {
+            ptr::copy_nonoverlapping(self.ptr, memory.as_mut_ptr() as *mut T, self.len);
+        }
+        self.ptr = memory.as_mut_ptr() as *mut T;
+        self.capacity = new_capacity;
+    }
+
+    /// Finishes the vector, returning its elements as a `[T]` with the
+    /// lifetime of the arena.
+    ///
+    /// The elements are never dropped after this.
+    pub fn into_slice(self) -> &'a mut [T] {
+        let this = mem::ManuallyDrop::new(self);
+
+        // Give any unused capacity back to the arena if we can.
+        if this.capacity > 0 {
+            this.arena.try_resize_in_place(
+                this.ptr as *const MaybeUninit<u8>,
+                this.capacity * size_of::<T>(),
+                this.len * size_of::<T>(),
+            );
+        }
+
+        unsafe { slice::from_raw_parts_mut(this.ptr, this.len) }
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> Drop for Vec<'a, T, A> {
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.ptr, self.len)) };
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> ops::Deref for Vec<'a, T, A> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> ops::DerefMut for Vec<'a, T, A> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> Extend<T> for Vec<'a, T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<'a, 'b, T: Copy + 'b, A: Allocator + Clone> Extend<&'b T> for Vec<'a, T, A> {
+    fn extend<I: IntoIterator<Item = &'b T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<'a, 'b, T> IntoIterator for &'b Vec<'a, T> {
+    type Item = &'b T;
+    type IntoIter = slice::Iter<'b, T>;
+
+    fn into_iter(self) -> slice::Iter<'b, T> {
+        self.iter()
+    }
+}
+
+impl<'a, 'b, T> IntoIterator for &'b mut Vec<'a, T> {
+    type Item = &'b mut T;
+    type IntoIter = slice::IterMut<'b, T>;
+
+    fn into_iter(self) -> slice::IterMut<'b, T> {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T: fmt::Debug, A: Allocator + Clone> fmt::Debug for Vec<'a, T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+impl<'a, T: PartialEq, A: Allocator + Clone> PartialEq<[T]> for Vec<'a, T, A> {
+    fn eq(&self, other: &[T]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<'a, 'b, T: PartialEq, A: Allocator + Clone> PartialEq<&'b [T]> for Vec<'a, T, A> {
+    fn eq(&self, other: &&'b [T]) -> bool {
+        self.as_slice() == *other
+    }
+}
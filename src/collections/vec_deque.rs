@@ -0,0 +1,336 @@
+use std::{
+    fmt,
+    iter::Chain,
+    mem::{size_of, MaybeUninit},
+    ops, ptr, slice,
+};
+
+use allocator_api2::alloc::{Allocator, Global};
+
+use crate::Arena;
+
+/// A double-ended queue stored in an arena, implemented as a ring buffer.
+///
+/// This works much like `std::collections::VecDeque`.  It grows as needed
+/// when using the `push_*()` methods, but can also be used with a fixed
+/// capacity by sticking to the `try_push_*()` methods, which never grow it.
+///
+/// ```rust
+/// # use kioku::{Arena, collections::VecDeque};
+/// let arena = Arena::new();
+/// let mut queue = VecDeque::with_capacity_in(2, &arena);
+/// assert_eq!(Ok(()), queue.try_push_back(1));
+/// assert_eq!(Ok(()), queue.try_push_back(2));
+/// assert_eq!(Err(3), queue.try_push_back(3));
+///
+/// assert_eq!(Some(1), queue.pop_front());
+/// queue.push_back(3);
+/// queue.push_front(0);
+/// assert_eq!(vec![0, 2, 3], queue.iter().copied().collect::<Vec<_>>());
+/// ```
+///
+/// Like `Vec`, the elements are dropped when the queue is dropped.
+pub struct VecDeque<'a, T, A: Allocator + Clone = Global> {
+    arena: &'a Arena<A>,
+    ptr: *mut T,
+    capacity: usize,
+    head: usize,
+    len: usize,
+}
+
+impl<'a, T, A: Allocator + Clone> VecDeque<'a, T, A> {
+    /// Creates a new empty queue.
+    ///
+    /// Doesn't allocate anything until something is added to it.
+    pub fn new_in(arena: &'a Arena<A>) -> VecDeque<'a, T, A> {
+        assert!(
+            size_of::<T>() > 0,
+            "`Arena` does not support zero-sized types."
+        );
+
+        VecDeque {
+            arena,
+            ptr: ptr::NonNull::dangling().as_ptr(),
+            capacity: 0,
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Creates a new empty queue with room for exactly `capacity` elements.
+    pub fn with_capacity_in(capacity: usize, arena: &'a Arena<A>) -> VecDeque<'a, T, A> {
+        let mut queue = VecDeque::new_in(arena);
+        if capacity > 0 {
+            queue.grow_to(capacity);
+        }
+        queue
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of elements the queue can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity
+    }
+
+    /// Appends `value` to the back of the queue.
+    pub fn push_back(&mut self, value: T) {
+        if self.is_full() {
+            self.reserve(1);
+        }
+        unsafe { self.ptr.add(self.physical_index(self.len)).write(value) };
+        self.len += 1;
+    }
+
+    /// Prepends `value` to the front of the queue.
+    pub fn push_front(&mut self, value: T) {
+        if self.is_full() {
+            self.reserve(1);
+        }
+        self.head = self.physical_index(self.capacity - 1);
+        unsafe { self.ptr.add(self.head).write(value) };
+        self.len += 1;
+    }
+
+    /// Appends `value` to the back of the queue if there's room, and
+    /// otherwise returns it as an error.
+    #[inline]
+    pub fn try_push_back(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+        self.push_back(value);
+        Ok(())
+    }
+
+    /// Prepends `value` to the front of the queue if there's room, and
+    /// otherwise returns it as an error.
+    #[inline]
+    pub fn try_push_front(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+        self.push_front(value);
+        Ok(())
+    }
+
+    /// Removes and returns the element at the back of the queue, if any.
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(unsafe { self.ptr.add(self.physical_index(self.len)).read() })
+    }
+
+    /// Removes and returns the element at the front of the queue, if any.
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let value = unsafe { self.ptr.add(self.head).read() };
+        self.head = self.physical_index(1);
+        self.len -= 1;
+        Some(value)
+    }
+
+    #[inline]
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    #[inline]
+    pub fn back(&self) -> Option<&T> {
+        self.len.checked_sub(1).and_then(|i| self.get(i))
+    }
+
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len {
+            Some(unsafe { &*self.ptr.add(self.physical_index(index)) })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.len {
+            Some(unsafe { &mut *self.ptr.add(self.physical_index(index)) })
+        } else {
+            None
+        }
+    }
+
+    /// The contents of the queue, in order, as two slices.
+    #[inline]
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (a, b) = self.slice_ranges();
+        unsafe {
+            (
+                slice::from_raw_parts(self.ptr.add(a.start), a.len()),
+                slice::from_raw_parts(self.ptr.add(b.start), b.len()),
+            )
+        }
+    }
+
+    /// The contents of the queue, in order, as two mutable slices.
+    #[inline]
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let (a, b) = self.slice_ranges();
+        unsafe {
+            (
+                slice::from_raw_parts_mut(self.ptr.add(a.start), a.len()),
+                slice::from_raw_parts_mut(self.ptr.add(b.start), b.len()),
+            )
+        }
+    }
+
+    pub fn iter(&self) -> Chain<slice::Iter<'_, T>, slice::Iter<'_, T>> {
+        let (a, b) = self.as_slices();
+        a.iter().chain(b.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> Chain<slice::IterMut<'_, T>, slice::IterMut<'_, T>> {
+        let (a, b) = self.as_mut_slices();
+        a.iter_mut().chain(b.iter_mut())
+    }
+
+    /// Empties the queue, keeping its capacity.
+    pub fn clear(&mut self) {
+        let (a, b) = self.as_mut_slices();
+        let (a, b) = (a as *mut [T], b as *mut [T]);
+        self.head = 0;
+        self.len = 0;
+        unsafe {
+            ptr::drop_in_place(a);
+            ptr::drop_in_place(b);
+        }
+    }
+
+    /// Ensures there is room for at least `additional` more elements without
+    /// reallocating.
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = self
+            .len
+            .checked_add(additional)
+            .expect("VecDeque capacity overflow");
+        if needed <= self.capacity {
+            return;
+        }
+
+        self.grow_to(needed.max(self.capacity * 2).max(4));
+    }
+
+    fn grow_to(&mut self, new_capacity: usize) {
+        let old_capacity = self.capacity;
+
+        // Try to grow in place first.  If that works, any wrapped-around
+        // elements at the start of the buffer are moved to just after the
+        // old end.  There's always room for them there, since the capacity
+        // at least doubles when there are any.
+        let new_size = new_capacity.checked_mul(size_of::<T>());
+        if old_capacity > 0
+            && new_size.is_some()
+            && self.arena.try_resize_in_place(
+                self.ptr as *const MaybeUninit<u8>,
+                old_capacity * size_of::<T>(),
+                new_size.unwrap(),
+            )
+        {
+            let (_, wrapped) = self.slice_ranges();
+            unsafe {
+                ptr::copy_nonoverlapping(self.ptr, self.ptr.add(old_capacity), wrapped.len());
+            }
+            self.capacity = new_capacity;
+            return;
+        }
+
+        // Otherwise move to a new buffer, unwrapping the contents in the
+        // process.
+        let memory = self.arena.alloc_array_uninit::<T>(new_capacity);
+        let new_ptr = memory.as_mut_ptr() as *mut T;
+        let (a, b) = self.slice_ranges();
+        unsafe {
+            ptr::copy_nonoverlapping(self.ptr.add(a.start), new_ptr, a.len());
+            ptr::copy_nonoverlapping(self.ptr.add(b.start), new_ptr.add(a.len()), b.len());
+        }
+        self.ptr = new_ptr;
+        self.capacity = new_capacity;
+        self.head = 0;
+    }
+
+    /// Physical buffer index of the logical index `index`, wrapping around.
+    #[inline(always)]
+    fn physical_index(&self, index: usize) -> usize {
+        let i = self.head + index;
+        if i >= self.capacity {
+            i - self.capacity
+        } else {
+            i
+        }
+    }
+
+    /// Physical index ranges of the contents, in order.
+    #[inline(always)]
+    fn slice_ranges(&self) -> (ops::Range<usize>, ops::Range<usize>) {
+        let end = self.head + self.len;
+        if end <= self.capacity {
+            (self.head..end, 0..0)
+        } else {
+            (self.head..self.capacity, 0..(end - self.capacity))
+        }
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> Drop for VecDeque<'a, T, A> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> ops::Index<usize> for VecDeque<'a, T, A> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("Index out of bounds")
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> ops::IndexMut<usize> for VecDeque<'a, T, A> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        self.get_mut(index).expect("Index out of bounds")
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> Extend<T> for VecDeque<'a, T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug, A: Allocator + Clone> fmt::Debug for VecDeque<'a, T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
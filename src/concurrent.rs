@@ -0,0 +1,279 @@
+use std::{
+    alloc::Layout,
+    collections::LinkedList,
+    fmt,
+    mem::{size_of, transmute, MaybeUninit},
+    ptr, slice,
+    sync::{
+        atomic::{AtomicPtr, AtomicUsize, Ordering},
+        Mutex, MutexGuard,
+    },
+};
+
+use crate::{alignment_offset, block::Block};
+
+/// An arena that many threads can allocate from with little contention.
+///
+/// Unlike `SyncArena`, allocation doesn't take a lock in the common case.
+/// Instead, threads bump-allocate from the current block with an atomic
+/// compare-and-swap, and a lock is only taken when the current block runs
+/// out of space and a new one needs to be added.
+///
+/// The trade-off is a much smaller API than `Arena`: only `Copy` types and
+/// raw memory, no resizing, and a simple constant block size.
+///
+/// ```rust
+/// # use kioku::ConcurrentArena;
+/// let arena = ConcurrentArena::new();
+/// let arrays: Vec<&mut [usize]> = std::thread::scope(|s| {
+///     let handles: Vec<_> = (0..4)
+///         .map(|i| {
+///             let arena = &arena;
+///             s.spawn(move || arena.alloc_array(i, 100))
+///         })
+///         .collect();
+///     handles.into_iter().map(|h| h.join().unwrap()).collect()
+/// });
+/// assert_eq!(&[3; 100][..], arrays[3]);
+/// ```
+pub struct ConcurrentArena {
+    /// The block currently being allocated from, or null if there isn't one.
+    /// Points into `blocks`.
+    current: AtomicPtr<SharedBlock>,
+    blocks: Mutex<LinkedList<SharedBlock>>,
+    block_size: usize,
+}
+
+/// A block whose fill level can be bumped from multiple threads at once.
+struct SharedBlock {
+    block: Block,
+    used: AtomicUsize,
+}
+
+impl SharedBlock {
+    /// Bump-allocates `layout` from the block, if there's room.
+    #[inline(always)]
+    fn bump(&self, layout: Layout) -> Option<*mut MaybeUninit<u8>> {
+        let base = self.block.as_ptr() as usize;
+        let mut used = self.used.load(Ordering::Relaxed);
+        loop {
+            let start = used + alignment_offset(base + used, layout.align());
+            let end = start.checked_add(layout.size())?;
+            if end > self.block.capacity() {
+                return None;
+            }
+
+            // Each successful exchange claims a disjoint range, so no
+            // stronger ordering is needed here.
+            match self
+                .used
+                .compare_exchange_weak(used, end, Ordering::Relaxed, Ordering::Relaxed)
+            {
+                Ok(_) => return Some((base + start) as *mut MaybeUninit<u8>),
+                Err(current_used) => used = current_used,
+            }
+        }
+    }
+}
+
+impl Default for ConcurrentArena {
+    fn default() -> ConcurrentArena {
+        ConcurrentArena::new()
+    }
+}
+
+impl fmt::Debug for ConcurrentArena {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ConcurrentArena")
+            .field("blocks.len():", &self.lock().len())
+            .field("block_size", &self.block_size)
+            .finish()
+    }
+}
+
+impl ConcurrentArena {
+    /// Create a new concurrent arena with default settings.
+    ///
+    /// - Block size: 64 KiB
+    pub fn new() -> ConcurrentArena {
+        ConcurrentArena {
+            current: AtomicPtr::new(ptr::null_mut()),
+            blocks: Mutex::new(LinkedList::new()),
+            block_size: 1 << 16, // 64 KiB
+        }
+    }
+
+    /// Build an arena with a specified block size in bytes.
+    ///
+    /// Allocations larger than a quarter of the block size are given their
+    /// own separate blocks.
+    pub fn with_block_size(self, block_size: usize) -> ConcurrentArena {
+        assert!(
+            block_size > 0,
+            "Block size must be greater \
+             than zero"
+        );
+        assert!(
+            self.lock().is_empty(),
+            "Cannot change block size after \
+             blocks have already been allocated"
+        );
+
+        ConcurrentArena { block_size, ..self }
+    }
+
+    /// Frees all memory currently allocated by the arena.
+    pub fn clear(&mut self) {
+        *self.current.get_mut() = ptr::null_mut();
+        self.blocks
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    /// Allocates a `T` initialized to `value`.
+    #[inline]
+    pub fn alloc<T: Copy>(&self, value: T) -> &mut T {
+        let memory = self.alloc_uninit();
+        unsafe {
+            *memory.as_mut_ptr() = value;
+        }
+        unsafe { transmute(memory) }
+    }
+
+    /// Allocates a `[T]` with all elements initialized to `value`.
+    #[inline]
+    pub fn alloc_array<T: Copy>(&self, value: T, len: usize) -> &mut [T] {
+        let memory = self.alloc_array_uninit(len);
+        for v in memory.iter_mut() {
+            unsafe {
+                *v.as_mut_ptr() = value;
+            }
+        }
+        unsafe { transmute(memory) }
+    }
+
+    /// Allocates a `[T]` with each element initialized to the return value of
+    /// `f` called with that element's index, in parallel.
+    ///
+    /// See `Arena::alloc_array_par_with()`.
+    #[cfg(feature = "rayon")]
+    pub fn alloc_array_par_with<T, F>(&self, len: usize, f: F) -> &mut [T]
+    where
+        T: Copy + Send,
+        F: Fn(usize) -> T + Sync + Send,
+    {
+        crate::par::fill(self.alloc_array_uninit(len), f)
+    }
+
+    /// Allocates a `[T]` initialized to the contents of `slice`.
+    #[inline]
+    pub fn copy_slice<T: Copy>(&self, slice: &[T]) -> &mut [T] {
+        let memory: &mut [MaybeUninit<T>] = self.alloc_array_uninit(slice.len());
+        unsafe {
+            ptr::copy_nonoverlapping(slice.as_ptr(), memory.as_mut_ptr() as *mut T, slice.len());
+            transmute(memory)
+        }
+    }
+
+    /// Allocates a `str` initialized to the contents of `text`.
+    #[inline]
+    pub fn copy_str(&self, text: &str) -> &mut str {
+        let bytes = self.copy_slice(text.as_bytes());
+        unsafe { std::str::from_utf8_unchecked_mut(bytes) }
+    }
+
+    /// Allocates an uninitialized `T`.
+    #[inline]
+    pub fn alloc_uninit<T>(&self) -> &mut MaybeUninit<T> {
+        assert!(
+            size_of::<T>() > 0,
+            "`ConcurrentArena` does not support zero-sized types."
+        );
+
+        let memory = self.alloc_raw(Layout::new::<T>()) as *mut MaybeUninit<T>;
+        unsafe { &mut *memory }
+    }
+
+    /// Allocates a uninitialized `[T]`.
+    #[inline]
+    pub fn alloc_array_uninit<T>(&self, len: usize) -> &mut [MaybeUninit<T>] {
+        assert!(
+            size_of::<T>() > 0,
+            "`ConcurrentArena` does not support zero-sized types."
+        );
+
+        let layout = Layout::array::<T>(len).expect("Array length overflow");
+        let memory = self.alloc_raw(layout) as *mut MaybeUninit<T>;
+        unsafe { slice::from_raw_parts_mut(memory, len) }
+    }
+
+    /// Allocates uninitialized memory with the given layout.
+    ///
+    /// Zero-sized layouts get a dangling (but properly aligned) pointer.
+    pub fn alloc_raw(&self, layout: Layout) -> *mut MaybeUninit<u8> {
+        if layout.size() == 0 {
+            return layout.align() as *mut MaybeUninit<u8>;
+        }
+
+        // Fast path: bump-allocate from the current block without locking.
+        let current = self.current.load(Ordering::Acquire);
+        if !current.is_null() {
+            if let Some(memory) = unsafe { (*current).bump(layout) } {
+                return memory;
+            }
+        }
+
+        self.alloc_raw_slow(layout)
+    }
+
+    /// Adds a new block for the allocation.
+    #[cold]
+    #[inline(never)]
+    fn alloc_raw_slow(&self, layout: Layout) -> *mut MaybeUninit<u8> {
+        let mut blocks = self.lock();
+
+        // Another thread may have already added a new block while we were
+        // waiting on the lock.
+        let current = self.current.load(Ordering::Acquire);
+        if !current.is_null() {
+            if let Some(memory) = unsafe { (*current).bump(layout) } {
+                return memory;
+            }
+        }
+
+        // Large allocations get their own block, so that they don't cut the
+        // current block short.
+        let is_large = layout.size() > self.block_size / 4;
+        let capacity = if is_large {
+            layout.size()
+        } else {
+            self.block_size
+        };
+
+        // The block is aligned for the allocation, so it goes at the start.
+        blocks.push_front(SharedBlock {
+            block: Block::new(capacity, layout.align().max(16)),
+            used: AtomicUsize::new(layout.size()),
+        });
+        let block = blocks.front().unwrap();
+
+        // Linked list nodes never move, so it's fine to point at the block
+        // until it's removed by `clear()`.
+        if !is_large {
+            self.current.store(
+                block as *const SharedBlock as *mut SharedBlock,
+                Ordering::Release,
+            );
+        }
+
+        block.block.as_ptr() as *mut MaybeUninit<u8>
+    }
+
+    #[inline(always)]
+    fn lock(&self) -> MutexGuard<'_, LinkedList<SharedBlock>> {
+        // A panic while locked can't leave the block list in an inconsistent
+        // state, so poisoning is ignored.
+        self.blocks.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,98 @@
+use std::{alloc::Layout, cell::Cell, mem::MaybeUninit, ptr};
+
+/// A list of arena-allocated values that need to be dropped.
+///
+/// The list itself is intrusive, with its nodes also allocated in the arena.
+/// Values are dropped in the reverse order they were added.
+pub(crate) struct DropList {
+    head: Cell<*mut DropNode>,
+}
+
+/// A position in a `DropList`, for rolling back to with `run_to()`.
+#[derive(Copy, Clone)]
+pub(crate) struct DropMark(*mut DropNode);
+
+struct DropNode {
+    drop_fn: unsafe fn(*mut u8),
+    value: *mut u8,
+    next: *mut DropNode,
+}
+
+// The only values that get added to the list are required to be `Send`.
+unsafe impl Send for DropList {}
+
+impl DropList {
+    pub fn new() -> DropList {
+        DropList {
+            head: Cell::new(ptr::null_mut()),
+        }
+    }
+
+    /// The layout of the memory needed for each node.
+    pub fn node_layout() -> Layout {
+        Layout::new::<DropNode>()
+    }
+
+    /// Adds `value` to the list, using `node_memory` to store the list node.
+    ///
+    /// # Safety
+    ///
+    /// `node_memory` must be allocated with `node_layout()`, and both it and
+    /// `value` must remain valid until the list is run or dropped.  `value`
+    /// must be initialized, and must not be dropped by anything else.
+    pub unsafe fn push<T>(&self, node_memory: *mut MaybeUninit<u8>, value: *mut T) {
+        unsafe fn drop_erased<T>(value: *mut u8) {
+            ptr::drop_in_place(value as *mut T);
+        }
+
+        let node = node_memory as *mut DropNode;
+        node.write(DropNode {
+            drop_fn: drop_erased::<T>,
+            value: value as *mut u8,
+            next: self.head.get(),
+        });
+        self.head.set(node);
+    }
+
+    /// Drops all of the values in the list, and empties it.
+    ///
+    /// # Safety
+    ///
+    /// None of the values in the list may be in use anymore.
+    pub unsafe fn run(&self) {
+        self.run_to(DropMark(ptr::null_mut()));
+    }
+
+    /// The current end of the list.
+    pub fn mark(&self) -> DropMark {
+        DropMark(self.head.get())
+    }
+
+    /// Drops the values added since `mark` was taken, and removes them from
+    /// the list.
+    ///
+    /// # Safety
+    ///
+    /// `mark` must have been taken from this list, and not been rolled back
+    /// past since.  None of the values added after it may be in use anymore.
+    pub unsafe fn run_to(&self, mark: DropMark) {
+        while self.head.get() != mark.0 {
+            let DropNode {
+                drop_fn,
+                value,
+                next,
+            } = self.head.get().read();
+
+            // Unlinked before dropping, so that the list stays consistent if
+            // the drop panics.
+            self.head.set(next);
+            drop_fn(value);
+        }
+    }
+}
+
+impl Drop for DropList {
+    fn drop(&mut self) {
+        unsafe { self.run() }
+    }
+}
@@ -0,0 +1,53 @@
+use std::{alloc::Layout, error, fmt};
+
+/// The error returned by the `try_*` allocation methods when memory can't
+/// be allocated.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AllocError;
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("memory allocation failed")
+    }
+}
+
+impl error::Error for AllocError {}
+
+impl From<ArenaAllocError> for AllocError {
+    fn from(_: ArenaAllocError) -> AllocError {
+        AllocError
+    }
+}
+
+/// The error returned by `Arena::try_alloc_raw()`, with the reason the
+/// allocation failed.
+///
+/// More reasons may be added in the future, so matches on it need a
+/// wildcard arm.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ArenaAllocError {
+    /// The size of the allocation, or of a block to hold it, was too large
+    /// to represent.
+    LayoutOverflow,
+    /// The backing allocator failed to allocate a block with this layout.
+    OutOfMemory(Layout),
+    /// A new block would have taken the arena over its memory limit.
+    MemoryLimitExceeded,
+}
+
+impl fmt::Display for ArenaAllocError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArenaAllocError::LayoutOverflow => f.write_str("allocation size overflow"),
+            ArenaAllocError::OutOfMemory(layout) => write!(
+                f,
+                "out of memory allocating a block of {} bytes",
+                layout.size()
+            ),
+            ArenaAllocError::MemoryLimitExceeded => f.write_str("arena memory limit exceeded"),
+        }
+    }
+}
+
+impl error::Error for ArenaAllocError {}
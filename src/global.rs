@@ -0,0 +1,208 @@
+//! Routing global heap allocations into an arena.
+//!
+//! This lets code you don't control, such as third-party libraries, have its
+//! temporary `Box`, `Vec`, `String`, etc. allocations made in an arena.  It
+//! requires installing `ScopedAlloc` as the global allocator:
+//!
+//! ```rust
+//! use kioku::{global::ScopedAlloc, Arena};
+//!
+//! #[global_allocator]
+//! static ALLOC: ScopedAlloc = ScopedAlloc::new();
+//!
+//! # fn main() {
+//! let arena = Arena::new();
+//! let len = unsafe {
+//!     kioku::global::with_arena(&arena, || {
+//!         // This `Vec` lives in the arena.
+//!         let v: Vec<u32> = (0..100).collect();
+//!         v.len()
+//!     })
+//! };
+//! assert_eq!(100, len);
+//! # }
+//! ```
+//!
+//! Outside of `with_arena()`, `ScopedAlloc` just forwards everything to the
+//! system allocator (or another allocator of your choosing).
+//!
+//! Only the current thread is affected.  Deallocating memory from the arena
+//! is a no-op, apart from the most recent allocation being given back.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    mem::MaybeUninit,
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+use crate::Arena;
+
+/// A global allocator that can be temporarily redirected to an arena.
+///
+/// See the module docs for details.
+pub struct ScopedAlloc<A = System> {
+    fallback: A,
+}
+
+impl ScopedAlloc<System> {
+    /// Creates an allocator that falls back to the system allocator.
+    pub const fn new() -> ScopedAlloc<System> {
+        ScopedAlloc { fallback: System }
+    }
+}
+
+impl Default for ScopedAlloc<System> {
+    fn default() -> ScopedAlloc<System> {
+        ScopedAlloc::new()
+    }
+}
+
+impl<A: GlobalAlloc> ScopedAlloc<A> {
+    /// Creates an allocator that falls back to `fallback`.
+    pub const fn with_fallback(fallback: A) -> ScopedAlloc<A> {
+        ScopedAlloc { fallback }
+    }
+}
+
+/// Runs `f`, with all heap allocations on the current thread made in `arena`.
+///
+/// This only has an effect if `ScopedAlloc` is installed as the global
+/// allocator.  Calls can be nested, in which case the innermost arena is
+/// used.
+///
+/// # Safety
+///
+/// Nothing allocated during the call may be used or deallocated after it
+/// returns, since the memory belongs to the arena.  In particular, no heap
+/// allocated values may escape `f` (including via its return value, statics,
+/// thread-locals, or channels) and the allocations must not be deallocated
+/// on other threads.  Keep in mind that this also applies to allocations
+/// made by libraries behind the scenes, such as lazily initialized caches.
+pub unsafe fn with_arena<R, F: FnOnce() -> R>(arena: &Arena, f: F) -> R {
+    struct Guard(*const Scope);
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            CURRENT.with(|current| current.set(unsafe { (*self.0).prev }));
+        }
+    }
+
+    let scope = Scope {
+        arena,
+        prev: CURRENT.with(|current| current.get()),
+    };
+    CURRENT.with(|current| current.set(&scope));
+    let _guard = Guard(&scope);
+
+    f()
+}
+
+/// Runs `f` with routing disabled, for bookkeeping allocations that an arena
+/// keeps past the end of the current scope.
+pub(crate) fn unscoped<R, F: FnOnce() -> R>(f: F) -> R {
+    struct Guard(*const Scope);
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            let _ = CURRENT.try_with(|current| current.set(self.0));
+        }
+    }
+
+    let _guard = Guard(current_scope());
+    let _ = CURRENT.try_with(|current| current.set(ptr::null()));
+    f()
+}
+
+/// One level of `with_arena()` nesting.
+struct Scope {
+    arena: *const Arena,
+    prev: *const Scope,
+}
+
+thread_local! {
+    static CURRENT: Cell<*const Scope> = const { Cell::new(ptr::null()) };
+}
+
+/// The innermost scope, if any.
+#[inline(always)]
+fn current_scope() -> *const Scope {
+    CURRENT
+        .try_with(|current| current.get())
+        .unwrap_or(ptr::null())
+}
+
+/// The arena of the innermost scope that owns `ptr`, if any.
+unsafe fn owning_arena<'a>(ptr: *const u8) -> Option<&'a Arena> {
+    let mut scope = current_scope();
+    while !scope.is_null() {
+        let arena = &*(*scope).arena;
+        if arena.owns(ptr) {
+            return Some(arena);
+        }
+        scope = (*scope).prev;
+    }
+    None
+}
+
+/// Runs `f` with routing disabled, so that the arena's own bookkeeping
+/// allocations go to the fallback allocator.
+///
+/// Returns null if `f` panics, since allocators must not unwind.
+unsafe fn unrouted<F: FnOnce() -> *mut u8>(f: F) -> *mut u8 {
+    let scope = current_scope();
+    CURRENT.with(|current| current.set(ptr::null()));
+    let result = panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(ptr::null_mut());
+    CURRENT.with(|current| current.set(scope));
+    result
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for ScopedAlloc<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let scope = current_scope();
+
+        // The arena is busy when it's allocating a new block for itself,
+        // which must come from the fallback allocator.
+        if scope.is_null() || (*(*scope).arena).is_busy() {
+            return self.fallback.alloc(layout);
+        }
+
+        let arena = &*(*scope).arena;
+        unrouted(|| arena.alloc_raw(layout) as *mut u8)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        match owning_arena(ptr) {
+            Some(arena) => {
+                // Give it back if it's the most recent allocation.
+                unrouted(|| {
+                    arena.try_resize_in_place(ptr as *const MaybeUninit<u8>, layout.size(), 0);
+                    ptr::null_mut()
+                });
+            }
+            None => self.fallback.dealloc(ptr, layout),
+        }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+
+        if let Some(arena) = owning_arena(ptr) {
+            return unrouted(|| {
+                arena.realloc_raw(ptr as *mut MaybeUninit<u8>, layout, new_layout) as *mut u8
+            });
+        }
+
+        let scope = current_scope();
+        if scope.is_null() || (*(*scope).arena).is_busy() {
+            return self.fallback.realloc(ptr, layout, new_size);
+        }
+
+        // Move memory from the fallback allocator into the arena.
+        let new_ptr = self.alloc(new_layout);
+        if !new_ptr.is_null() {
+            ptr::copy_nonoverlapping(ptr, new_ptr, layout.size().min(new_size));
+            self.fallback.dealloc(ptr, layout);
+        }
+        new_ptr
+    }
+}
@@ -0,0 +1,164 @@
+use std::{
+    any::TypeId,
+    cell::{Cell, RefCell},
+    convert::TryFrom,
+    fmt, hash,
+    marker::PhantomData,
+};
+
+/// A small, copyable reference to a value allocated with
+/// `Arena::alloc_handle()`.
+///
+/// Unlike a normal reference, a handle doesn't borrow the arena, so the arena
+/// can be cleared while handles to it still exist.  Accessing a value through
+/// its handle is checked: after the value is freed, `Arena::get()` and
+/// `Arena::get_mut()` return `None`.
+///
+/// ```rust
+/// # use kioku::Arena;
+/// let mut arena = Arena::new();
+/// let handle = arena.alloc_handle(42u32);
+/// *arena.get_mut(handle).unwrap() += 1;
+/// assert_eq!(Some(&43), arena.get(handle));
+///
+/// arena.clear();
+/// assert_eq!(None, arena.get(handle));
+/// ```
+///
+/// Handles are only meaningful for the arena that created them.  Using a
+/// handle with a different arena is safe, but may return an unrelated value.
+pub struct Handle<T> {
+    index: u32,
+    generation: u32,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    /// The index of the handle within its arena.
+    #[inline]
+    pub fn index(self) -> u32 {
+        self.index
+    }
+
+    /// The generation of the handle, which distinguishes it from handles
+    /// with the same index from before the arena was cleared.
+    #[inline]
+    pub fn generation(self) -> u32 {
+        self.generation
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    #[inline]
+    fn clone(&self) -> Handle<T> {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    #[inline]
+    fn eq(&self, other: &Handle<T>) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> hash::Hash for Handle<T> {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Handle")
+            .field("index", &self.index)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+/// The arena's table of handle-allocated values.
+pub(crate) struct HandleTable {
+    slots: RefCell<Vec<Slot>>,
+    generation: Cell<u32>,
+}
+
+// The pointers all point into the arena's own memory, and only to `Send`
+// values.
+unsafe impl Send for HandleTable {}
+
+struct Slot {
+    ptr: *mut u8,
+    generation: u32,
+    // Checked on access, so that using a handle with the wrong arena can't
+    // reinterpret a value as a different type.
+    type_id: TypeId,
+}
+
+impl HandleTable {
+    pub fn new() -> HandleTable {
+        HandleTable {
+            slots: RefCell::new(Vec::new()),
+            generation: Cell::new(0),
+        }
+    }
+
+    /// Registers `ptr` and returns a handle to it.
+    pub fn insert<T: 'static>(&self, ptr: *mut T) -> Handle<T> {
+        let mut slots = self.slots.borrow_mut();
+        let index = u32::try_from(slots.len()).expect("Too many handles");
+        slots.push(Slot {
+            ptr: ptr as *mut u8,
+            generation: self.generation.get(),
+            type_id: TypeId::of::<T>(),
+        });
+        Handle {
+            index,
+            generation: self.generation.get(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The pointer for `handle`, if it's still valid.
+    pub fn get<T: 'static>(&self, handle: Handle<T>) -> Option<*mut T> {
+        let slots = self.slots.borrow();
+        let slot = slots.get(handle.index as usize)?;
+        if slot.generation == handle.generation && slot.type_id == TypeId::of::<T>() {
+            Some(slot.ptr as *mut T)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the table is in the middle of being modified.
+    pub fn is_busy(&self) -> bool {
+        self.slots.try_borrow_mut().is_err()
+    }
+
+    /// The number of handles currently registered.
+    pub fn len(&self) -> usize {
+        self.slots.borrow().len()
+    }
+
+    /// Invalidates all handles from index `len` onwards.
+    pub fn truncate(&self, len: usize) {
+        let mut slots = self.slots.borrow_mut();
+        if len < slots.len() {
+            slots.truncate(len);
+
+            // Indices will be reused, so new handles need a new generation
+            // to tell them apart from the invalidated ones.
+            let generation = self.generation.get();
+            self.generation.set(
+                generation
+                    .checked_add(1)
+                    .expect("Handle generation overflow"),
+            );
+        }
+    }
+}
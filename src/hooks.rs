@@ -0,0 +1,44 @@
+use std::alloc::Layout;
+
+/// Callbacks for observing an arena's activity, e.g. for telemetry.
+///
+/// Registered with `Arena::with_hooks()`.  All of the methods do nothing by
+/// default, so implementations only need to provide the ones they're
+/// interested in.
+///
+/// The hooks are called while the arena is in the middle of allocating, so
+/// they must not use the arena themselves.  Allocating from it will panic,
+/// and using it in other ways may panic.
+///
+/// ```rust
+/// # use kioku::{Arena, ArenaHooks};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+///
+/// struct BlockCounter(Arc<AtomicUsize>);
+///
+/// impl ArenaHooks for BlockCounter {
+///     fn on_new_block(&self, _size: usize, _oversize: bool) {
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let count = Arc::new(AtomicUsize::new(0));
+/// let arena = Arena::new().with_hooks(BlockCounter(count.clone()));
+/// arena.alloc(1u32);
+/// arena.alloc_array(0u8, 100_000);
+///
+/// // The first shared block, and a one-off block for the big array.
+/// assert_eq!(2, count.load(Ordering::Relaxed));
+/// ```
+pub trait ArenaHooks: Send {
+    /// Called for every allocation, before it's made.
+    fn on_alloc(&self, _layout: Layout) {}
+
+    /// Called when the arena allocates a new block of `size` bytes.
+    ///
+    /// `oversize` is whether the block is for a single oversized allocation,
+    /// rather than shared between allocations.  Blocks reused from a
+    /// previous `reset()` don't count as new.
+    fn on_new_block(&self, _size: usize, _oversize: bool) {}
+}
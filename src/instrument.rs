@@ -0,0 +1,153 @@
+use std::{collections::HashMap, fmt, panic::Location};
+
+const BUCKETS: usize = usize::BITS as usize;
+
+/// A histogram of the sizes and alignments of an arena's allocations, from
+/// `Arena::alloc_histogram()`.
+///
+/// Only available with the `instrument` feature.
+///
+/// Both are bucketed by powers of two.  Alignments are always powers of two,
+/// so each alignment gets its own bucket.  Sizes are rounded up to the next
+/// power of two, so e.g. the 16-byte bucket holds allocations of 9 to 16
+/// bytes.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct AllocHistogram {
+    sizes: [u64; BUCKETS],
+    alignments: [u64; BUCKETS],
+}
+
+impl AllocHistogram {
+    const fn new() -> AllocHistogram {
+        AllocHistogram {
+            sizes: [0; BUCKETS],
+            alignments: [0; BUCKETS],
+        }
+    }
+
+    #[inline(always)]
+    fn record(&mut self, size: usize, align: usize) {
+        self.sizes[size.next_power_of_two().trailing_zeros() as usize] += 1;
+        self.alignments[align.trailing_zeros() as usize] += 1;
+    }
+
+    /// The allocation counts by size, as `(size, count)` pairs.
+    ///
+    /// Each size is the upper bound (inclusive) of its bucket.  Only
+    /// non-empty buckets are included.
+    pub fn sizes(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        buckets(&self.sizes)
+    }
+
+    /// The allocation counts by alignment, as `(alignment, count)` pairs.
+    ///
+    /// Only non-empty buckets are included.
+    pub fn alignments(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        buckets(&self.alignments)
+    }
+}
+
+impl Default for AllocHistogram {
+    fn default() -> AllocHistogram {
+        AllocHistogram::new()
+    }
+}
+
+/// The allocations made from a single place in the code, from
+/// `Arena::call_sites()`.
+///
+/// Only available with the `instrument` feature.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CallSite {
+    /// Where the allocations were made.
+    pub location: &'static Location<'static>,
+
+    /// The number of allocations made.
+    pub alloc_count: u64,
+
+    /// The total size of the allocations, in bytes.
+    pub allocated: u64,
+}
+
+impl fmt::Display for CallSite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} bytes in {} allocations",
+            self.location, self.allocated, self.alloc_count
+        )
+    }
+}
+
+/// Everything recorded about an arena's allocations.
+pub(crate) struct Instrumentation {
+    pub histogram: AllocHistogram,
+    call_sites: HashMap<&'static Location<'static>, (u64, u64)>,
+}
+
+impl Instrumentation {
+    pub fn new() -> Instrumentation {
+        Instrumentation {
+            histogram: AllocHistogram::new(),
+            call_sites: HashMap::new(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn record(&mut self, size: usize, align: usize, location: &'static Location<'static>) {
+        self.histogram.record(size, align);
+        let (count, bytes) = self.call_sites.entry(location).or_insert((0, 0));
+        *count = count.saturating_add(1);
+        *bytes = bytes.saturating_add(size as u64);
+    }
+
+    /// The call sites, largest total allocation size first.
+    pub fn call_sites(&self) -> Vec<CallSite> {
+        let mut call_sites: Vec<CallSite> = self
+            .call_sites
+            .iter()
+            .map(|(&location, &(alloc_count, allocated))| CallSite {
+                location,
+                alloc_count,
+                allocated,
+            })
+            .collect();
+        call_sites.sort_by(|a, b| {
+            b.allocated
+                .cmp(&a.allocated)
+                .then_with(|| a.location.cmp(b.location))
+        });
+        call_sites
+    }
+}
+
+fn buckets(counts: &[u64; BUCKETS]) -> impl Iterator<Item = (usize, u64)> + '_ {
+    counts
+        .iter()
+        .enumerate()
+        .filter(|(_, &count)| count > 0)
+        .map(|(i, &count)| (1 << i, count))
+}
+
+impl fmt::Debug for AllocHistogram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AllocHistogram")
+            .field("sizes", &self.sizes().collect::<Vec<_>>())
+            .field("alignments", &self.alignments().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl fmt::Display for AllocHistogram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Sizes:")?;
+        for (size, count) in self.sizes() {
+            writeln!(f, "  <= {:>8} bytes: {}", size, count)?;
+        }
+        writeln!(f, "Alignments:")?;
+        for (align, count) in self.alignments() {
+            writeln!(f, "  {:>11} bytes: {}", align, count)?;
+        }
+        Ok(())
+    }
+}
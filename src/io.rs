@@ -0,0 +1,141 @@
+use std::{io, mem::MaybeUninit, slice};
+
+use allocator_api2::alloc::{Allocator, Global};
+
+use crate::{collections::Vec, Arena};
+
+/// An `io::Write` implementation that writes into arena memory.
+///
+/// The written bytes can be retrieved as a plain `&mut [u8]` with the lifetime
+/// of the arena via `into_slice()`.
+///
+/// ```rust
+/// # use kioku::{Arena, ArenaWriter};
+/// use std::io::Write;
+///
+/// let arena = Arena::new();
+/// let mut writer = ArenaWriter::new(&arena);
+/// writer.write_all(&[1, 2, 3]).unwrap();
+/// writer.write_all(&[4, 5]).unwrap();
+///
+/// let bytes: &mut [u8] = writer.into_slice();
+/// assert_eq!(&[1, 2, 3, 4, 5], bytes);
+/// ```
+///
+/// Like the growable collections, it grows in place when its buffer is the
+/// most recent allocation in the arena, and otherwise moves to a new buffer.
+pub struct ArenaWriter<'a, A: Allocator + Clone = Global> {
+    vec: Vec<'a, u8, A>,
+}
+
+impl<'a, A: Allocator + Clone> ArenaWriter<'a, A> {
+    /// Creates a new empty writer.
+    ///
+    /// Doesn't allocate anything until something is written to it.
+    pub fn new(arena: &'a Arena<A>) -> ArenaWriter<'a, A> {
+        ArenaWriter {
+            vec: Vec::new_in(arena),
+        }
+    }
+
+    /// Creates a new empty writer with room for at least `capacity` bytes.
+    pub fn with_capacity_in(capacity: usize, arena: &'a Arena<A>) -> ArenaWriter<'a, A> {
+        ArenaWriter {
+            vec: Vec::with_capacity_in(capacity, arena),
+        }
+    }
+
+    /// Number of bytes written so far.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// The bytes written so far.
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        self.vec.as_slice()
+    }
+
+    /// Ensures there is room for at least `additional` more bytes without
+    /// reallocating.
+    pub fn reserve(&mut self, additional: usize) {
+        self.vec.reserve(additional);
+    }
+
+    /// Reads everything from `reader` until EOF, appending it.
+    pub(crate) fn read_to_end<R: io::Read + ?Sized>(&mut self, reader: &mut R) -> io::Result<()> {
+        // Number of bytes of the spare capacity that have been initialized,
+        // since `Read` requires initialized memory to read into.
+        let mut init_len = 0;
+
+        loop {
+            if self.vec.len() == self.vec.capacity() {
+                // The buffer may be exactly the right size already, so check
+                // for EOF with a small probe before growing it, like std's
+                // `read_to_end()` does.
+                let mut probe = [0u8; 32];
+                match reader.read(&mut probe) {
+                    Ok(0) => return Ok(()),
+                    Ok(n) => {
+                        assert!(n <= probe.len(), "Reader reported reading too many bytes");
+                        self.vec.extend_from_slice_copy(&probe[..n]);
+                        init_len = 0;
+                    }
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+                continue;
+            }
+
+            let spare = self.vec.spare_capacity_mut();
+            for byte in &mut spare[init_len..] {
+                *byte = MaybeUninit::new(0);
+            }
+            init_len = spare.len();
+            let spare =
+                unsafe { slice::from_raw_parts_mut(spare.as_mut_ptr() as *mut u8, spare.len()) };
+
+            match reader.read(spare) {
+                Ok(0) => return Ok(()),
+                Ok(n) => {
+                    assert!(n <= init_len, "Reader reported reading too many bytes");
+                    unsafe { self.vec.set_len(self.vec.len() + n) };
+                    init_len -= n;
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Finishes writing, returning the written bytes with the lifetime of the
+    /// arena.
+    pub fn into_slice(self) -> &'a mut [u8] {
+        self.vec.into_slice()
+    }
+
+    /// Like `into_slice()`, but returns the written bytes as a `BStr`.
+    ///
+    /// Only available with the `bstr` feature.
+    #[cfg(feature = "bstr")]
+    pub fn into_bstr(self) -> &'a mut bstr::BStr {
+        self.into_slice().as_mut()
+    }
+}
+
+impl<'a, A: Allocator + Clone> io::Write for ArenaWriter<'a, A> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.vec.extend_from_slice_copy(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
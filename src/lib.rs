@@ -48,18 +48,81 @@
 //! the array to that alignment, and otherwise follow standard array memory
 //! layout.
 //!
+//! # Optional Features
+//!
+//! - `bstr`: adds methods for working with byte strings from the
+//!   [bstr](https://crates.io/crates/bstr) crate.
+//! - `bytemuck`: adds safe methods for zeroed allocation and byte
+//!   reinterpretation, using the traits from the
+//!   [bytemuck](https://crates.io/crates/bytemuck) crate.
+//! - `zerocopy`: adds safe methods for copying bytes into the arena as typed
+//!   data, using the traits from the
+//!   [zerocopy](https://crates.io/crates/zerocopy) crate.
+//! - `allocator-api2`: implements the `Allocator` trait from the
+//!   [allocator-api2](https://crates.io/crates/allocator-api2) crate for
+//!   `&Arena`, so that its collections can be stored in the arena.
+//! - `allocator_api`: implements the standard library's `Allocator` trait for
+//!   `&Arena`, so that `Vec`, `Box`, etc. can be stored in the arena.  This
+//!   requires a nightly compiler.
+//! - `hashbrown`: adds `HashMap` and `HashSet` types to the `collections`
+//!   module, which store their tables in the arena, using the
+//!   [hashbrown](https://crates.io/crates/hashbrown) crate.  Implies
+//!   `allocator-api2`.
+//! - `rayon`: adds methods for initializing arrays in parallel, using the
+//!   [rayon](https://crates.io/crates/rayon) crate.
+//! - `tracing`: emits debug-level events for new blocks and oversized
+//!   allocations, using the [tracing](https://crates.io/crates/tracing)
+//!   crate.
+//! - `mmap`: adds `MmapSource`, a block source that maps memory directly
+//!   from the operating system, optionally with huge pages.  Only has an
+//!   effect on Unix.
+//! - `guard_pages`: adds `Arena::with_guard_pages()`, which surrounds
+//!   oversized allocations with inaccessible memory to catch out-of-bounds
+//!   writes.  Implies `mmap`, and likewise only has an effect on Unix.
+//! - `instrument`: records a histogram of allocation sizes and alignments,
+//!   and how much each place in the code allocates, for tuning an arena's
+//!   settings and tracking down excessive memory use.  This adds overhead to
+//!   every allocation.
+//!
+//! Both allocator implementations only reclaim memory on deallocation for the
+//! most recent allocation.  Otherwise memory is reclaimed when the arena is
+//! cleared or dropped, as usual.  Values stored in collections with those
+//! allocators are dropped normally by the collections themselves.
+//!
+//! # Non-Copy Types
+//!
+//! The arena doesn't normally run `Drop` for anything allocated in it, which is
+//! why most allocation methods require `T: Copy`.  The `*_clone()` and
+//! `*_uninit()` methods relax this, but anything with a destructor stored in
+//! their allocations is leaked (its destructor is never run) when the arena is
+//! cleared or dropped.
+//!
+//! The exception is `alloc_droppable()`, which registers its allocation with
+//! the arena so that it's properly dropped when the arena is cleared or
+//! dropped.  This costs a little extra space per allocation, and requires
+//! `T: Send + 'static`.
+//!
+//! Alternatively, `ABox` owns a value in the arena and drops it when the box
+//! itself is dropped, like a normal `Box`.  Similarly, `ARc` provides shared
+//! ownership of a value in the arena, like a normal `Rc`.
+//!
+//! And for storing lots of values of a single type, `TypedArena` drops
+//! everything in it when it's dropped.
+//!
 //! # Zero Sized Types
 //!
 //! Zero-sized types such as `()` are unsupported.  All allocations will panic
-//! if `T` is zero-sized.
+//! if `T` is zero-sized, with the exception of `alloc_closure()`.
 //!
 //! However, you *can* allocate zero length arrays using the array allocation
 //! methods.  Only `T` itself must be non-zero-sized.
 
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
 // Normally I agree with this lint, but in this particular library's case it
 // just gets too noisy not using transmute.  It actually obscures intent when
 // reading the code.
 #![allow(clippy::transmute_ptr_to_ptr)]
+#![allow(clippy::missing_transmute_annotations)]
 // Disabling this particular clippy warning requires more significant
 // explaination.
 //
@@ -75,34 +138,172 @@
 // sound.  Thus, disabling the lint.
 #![allow(clippy::mut_from_ref)]
 
+#[cfg(any(feature = "allocator_api", feature = "allocator-api2"))]
+mod allocator;
+mod arrays;
+mod block;
+mod boxed;
+mod buffer_arena;
+pub mod collections;
+mod concurrent;
+mod drop_list;
+mod error;
+pub mod global;
+mod handle;
+mod hooks;
+#[cfg(feature = "instrument")]
+mod instrument;
+mod io;
+#[cfg(all(unix, feature = "mmap"))]
+mod mmap;
+mod offset_ptr;
+#[cfg(feature = "rayon")]
+mod par;
+mod rc;
+mod scratch;
+mod sharded;
+mod source;
+mod stack;
+mod stack_arena;
+mod stats;
+mod sub_arena;
+mod sync;
+mod temp;
+mod typed_arena;
+
+pub use arrays::{Array2d, StridedArray};
+pub use boxed::ABox;
+pub use buffer_arena::BufferArena;
+pub use concurrent::ConcurrentArena;
+pub use error::{AllocError, ArenaAllocError};
+pub use handle::Handle;
+pub use hooks::ArenaHooks;
+#[cfg(feature = "instrument")]
+pub use instrument::{AllocHistogram, CallSite};
+pub use io::ArenaWriter;
+#[cfg(all(unix, feature = "mmap"))]
+pub use mmap::{HugePages, MmapSource};
+pub use offset_ptr::ArenaOffsetPtr;
+pub use rc::ARc;
+pub use scratch::{thread_local_arena, with_scratch, ScratchArena};
+pub use sharded::ShardedArena;
+pub use source::{BlockSource, BufferSource, HeapSource};
+pub use stack::StackBox;
+pub use stack_arena::StackArena;
+pub use stats::{ArenaMetrics, ArenaStats};
+pub use sub_arena::SubArena;
+pub use sync::SyncArena;
+pub use temp::TempScope;
+pub use typed_arena::TypedArena;
+
+use allocator_api2::alloc::{Allocator, Global};
+use block::{Block, BlockList};
+use drop_list::{DropList, DropMark};
+use handle::HandleTable;
+use sub_arena::ParentRef;
+
 use std::{
     alloc::Layout,
     cell::{Cell, RefCell},
+    char::DecodeUtf16Error,
     collections::LinkedList,
+    ffi::CStr,
     fmt,
-    mem::{size_of, transmute, MaybeUninit},
+    mem::{size_of, size_of_val, transmute, MaybeUninit},
+    ptr::NonNull,
     slice,
+    sync::Arc,
 };
 
+/// The cache line size in bytes of the target architecture.
+///
+/// This is a best guess based on the target at compile time, and is intended
+/// for use as an alignment that avoids false sharing between allocations.
+#[cfg(any(
+    all(target_arch = "aarch64", target_vendor = "apple"),
+    target_arch = "powerpc64",
+))]
+pub const CACHE_LINE: usize = 128;
+#[cfg(target_arch = "s390x")]
+pub const CACHE_LINE: usize = 256;
+#[cfg(not(any(
+    all(target_arch = "aarch64", target_vendor = "apple"),
+    target_arch = "powerpc64",
+    target_arch = "s390x",
+)))]
+pub const CACHE_LINE: usize = 64;
+
 /// A memory arena allocator.
-#[derive(Default)]
-pub struct Arena {
-    blocks: RefCell<LinkedList<Vec<MaybeUninit<u8>>>>,
+///
+/// `A` is the allocator that the arena's blocks come from, which is the
+/// global allocator by default.  See `new_in()`.
+pub struct Arena<A: Allocator + Clone = Global> {
+    // Must come before `blocks`, so that the values it drops are still
+    // valid when it's dropped.
+    drop_list: DropList,
+    blocks: RefCell<BlockList<A>>,
+    handles: HandleTable,
+    regions: RefCell<LinkedList<(String, Arena<A>)>>,
+    /// Empty blocks kept for reuse by `reset()`.
+    spare_blocks: RefCell<Vec<Block<A>>>,
+    /// Where new blocks come from, if this is a sub-arena.
+    parent: Option<ParentRef<A>>,
+    /// Where new blocks come from, if there's no parent or block source.
+    allocator: A,
     min_block_size: usize,
+    block_alignment: usize,
     growth_strategy: GrowthStrategy,
+    retention_policy: RetentionPolicy,
     max_waste_percentage: usize,
+    hooks: Option<Box<dyn ArenaHooks>>,
+    /// Whether one of the hooks is currently running.
+    in_hooks: Cell<bool>,
+    /// Where new blocks come from, if not the global allocator.
+    block_source: Option<Arc<dyn BlockSource>>,
+    /// Where blocks for oversized allocations come from, if they're guarded.
+    #[cfg(all(unix, feature = "guard_pages"))]
+    guard_source: Option<Arc<dyn BlockSource>>,
+    memory_limit: Option<usize>,
+    oom_policy: OomPolicy,
     stat_space_occupied: Cell<usize>,
     stat_space_allocated: Cell<usize>,
+    stat_oversize_block_count: Cell<usize>,
+    stat_oversize_allocated: Cell<usize>,
+    // Counts are 64-bit so that long-lived arenas can't wrap them on 32-bit
+    // targets.
+    stat_alloc_count: Cell<u64>,
+    stat_total_alloc_count: Cell<u64>,
+    stat_peak_allocated: Cell<usize>,
+    stat_peak_block_count: Cell<usize>,
+    #[cfg(feature = "instrument")]
+    instrumentation: RefCell<instrument::Instrumentation>,
 }
 
-impl fmt::Debug for Arena {
+impl Default for Arena {
+    fn default() -> Arena {
+        Arena::new()
+    }
+}
+
+impl<A: Allocator + Clone> fmt::Debug for Arena<A> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("Arena")
             .field("blocks.len():", &self.blocks.borrow().len())
             .field("min_block_size", &self.min_block_size)
+            .field("block_alignment", &self.block_alignment)
             .field("max_waste_percentage", &self.max_waste_percentage)
+            .field("retention_policy", &self.retention_policy)
+            .field("hooks.is_some()", &self.hooks.is_some())
+            .field("memory_limit", &self.memory_limit)
+            .field("oom_policy", &self.oom_policy)
             .field("stat_space_occupied", &self.stat_space_occupied)
             .field("stat_space_allocated", &self.stat_space_allocated)
+            .field("stat_oversize_block_count", &self.stat_oversize_block_count)
+            .field("stat_oversize_allocated", &self.stat_oversize_allocated)
+            .field("stat_alloc_count", &self.stat_alloc_count)
+            .field("stat_total_alloc_count", &self.stat_total_alloc_count)
+            .field("stat_peak_allocated", &self.stat_peak_allocated)
+            .field("stat_peak_block_count", &self.stat_peak_block_count)
             .finish()
     }
 }
@@ -111,21 +312,67 @@ impl Arena {
     /// Create a new arena with default settings.
     ///
     /// - Initial block size: 1 KiB
+    /// - Block alignment: 16 bytes
     /// - Growth strategy: constant
     /// - Maximum waste percentage: 20 percent
+    /// - Retention policy: nothing
     pub fn new() -> Arena {
+        Arena::new_in(Global)
+    }
+}
+
+impl<A: Allocator + Clone> Arena<A> {
+    /// Create a new arena with default settings, which allocates its blocks
+    /// from `allocator` rather than the global allocator.
+    ///
+    /// The allocator is cloned for each block, so that it can be given back,
+    /// which makes references to a shared pool (`&Pool`) a good fit.  The
+    /// allocator is only called when a block is allocated or freed, not for
+    /// individual allocations.  Regions and sub-arenas use it as well.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// use allocator_api2::alloc::Global;
+    ///
+    /// let arena = Arena::new_in(Global);
+    /// assert_eq!(42, *arena.alloc(42u32));
+    /// ```
+    pub fn new_in(allocator: A) -> Arena<A> {
         Arena {
-            blocks: RefCell::new(LinkedList::new()),
+            drop_list: DropList::new(),
+            blocks: RefCell::new(BlockList::new()),
+            handles: HandleTable::new(),
+            regions: RefCell::new(LinkedList::new()),
+            spare_blocks: RefCell::new(Vec::new()),
+            parent: None,
+            allocator,
             min_block_size: 1 << 10, // 1 KiB,
+            block_alignment: 16,
             growth_strategy: GrowthStrategy::Constant,
             max_waste_percentage: 20,
+            retention_policy: RetentionPolicy::Nothing,
+            hooks: None,
+            in_hooks: Cell::new(false),
+            block_source: None,
+            #[cfg(all(unix, feature = "guard_pages"))]
+            guard_source: None,
+            memory_limit: None,
+            oom_policy: OomPolicy::Abort,
             stat_space_occupied: Cell::new(0),
             stat_space_allocated: Cell::new(0),
+            stat_oversize_block_count: Cell::new(0),
+            stat_oversize_allocated: Cell::new(0),
+            stat_alloc_count: Cell::new(0),
+            stat_total_alloc_count: Cell::new(0),
+            stat_peak_allocated: Cell::new(0),
+            stat_peak_block_count: Cell::new(0),
+            #[cfg(feature = "instrument")]
+            instrumentation: RefCell::new(instrument::Instrumentation::new()),
         }
     }
 
     /// Build an arena with a specified block size in bytes.
-    pub fn with_block_size(self, block_size: usize) -> Arena {
+    pub fn with_block_size(self, block_size: usize) -> Arena<A> {
         assert!(
             block_size > 0,
             "Initial block size must be greater \
@@ -143,6 +390,30 @@ impl Arena {
         }
     }
 
+    /// Build an arena with a specified block alignment in bytes.
+    ///
+    /// The base address of every block will be aligned to at least this.
+    /// Setting it to the largest alignment you commonly allocate with avoids
+    /// wasting space on padding at the start of each block.
+    ///
+    /// Must be greater than zero and a power of two.
+    pub fn with_block_alignment(self, block_alignment: usize) -> Arena<A> {
+        assert!(
+            block_alignment.is_power_of_two(),
+            "Invalid alignment: not a power of two."
+        );
+        assert!(
+            self.blocks.borrow().is_empty(),
+            "Cannot change block alignment after \
+             blocks have already been allocated"
+        );
+
+        Arena {
+            block_alignment,
+            ..self
+        }
+    }
+
     /// Build an arena with a specified maximum waste percentage.
     ///
     /// - Recommended values are between 10 and 30.
@@ -152,7 +423,7 @@ impl Arena {
     ///   likely trigger a lot of one-off non-arena allocations even for small
     ///   allocation requests, which defeats the whole purpose of using a memory
     ///   arena.
-    pub fn with_max_waste_percentage(self, max_waste_percentage: usize) -> Arena {
+    pub fn with_max_waste_percentage(self, max_waste_percentage: usize) -> Arena<A> {
         assert!(
             max_waste_percentage > 0 && max_waste_percentage <= 100,
             "The max waste percentage must be between 1 and 100"
@@ -165,18 +436,186 @@ impl Arena {
     }
 
     /// Build an arena with a specified memory block growth strategy.
-    pub fn with_growth_strategy(self, growth_strategy: GrowthStrategy) -> Arena {
+    pub fn with_growth_strategy(self, growth_strategy: GrowthStrategy) -> Arena<A> {
         Arena {
             growth_strategy,
             ..self
         }
     }
 
+    /// Build an arena with a specified policy for which blocks `clear()`
+    /// keeps for reuse.
+    pub fn with_retention_policy(self, retention_policy: RetentionPolicy) -> Arena<A> {
+        Arena {
+            retention_policy,
+            ..self
+        }
+    }
+
+    /// Build an arena that can't occupy more than `limit` bytes.
+    ///
+    /// Allocations that would need a new block past the limit panic, or
+    /// return an error from the `try_*` methods.  The limit is on all of the
+    /// memory the arena's blocks take up, including blocks kept for reuse
+    /// by `reset()` or the retention policy, not the bytes actually
+    /// allocated, so it's reached sooner when there's waste.  Blocks kept
+    /// for reuse are freed if that makes enough room.  Regions each get the
+    /// same limit, and a sub-arena's blocks count towards its parent's limit.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let arena = Arena::new().with_block_size(1024).with_memory_limit(4096);
+    /// assert!(arena.try_alloc_array(0u8, 3000).is_ok());
+    /// assert!(arena.try_alloc_array(0u8, 3000).is_err());
+    /// ```
+    pub fn with_memory_limit(self, limit: usize) -> Arena<A> {
+        Arena {
+            memory_limit: Some(limit),
+            ..self
+        }
+    }
+
+    /// Build an arena with a specified policy for when the backing
+    /// allocator fails.
+    ///
+    /// ```rust
+    /// # use kioku::{Arena, OomPolicy};
+    /// let arena = Arena::new().with_oom_policy(OomPolicy::Panic);
+    /// let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+    ///     arena.alloc_array(0u8, isize::MAX as usize / 2);
+    /// }));
+    /// assert!(result.is_err());
+    /// ```
+    pub fn with_oom_policy(self, oom_policy: OomPolicy) -> Arena<A> {
+        Arena { oom_policy, ..self }
+    }
+
+    /// Build an arena that allocates its blocks from `allocator`, rather
+    /// than the global allocator.
+    ///
+    /// Same as `new_in()`, but can be combined with the other settings.
+    /// Since the arena's type changes, this has to be done before anything
+    /// is allocated.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// use allocator_api2::alloc::{AllocError, Allocator, Global};
+    /// use std::{alloc::Layout, cell::Cell, ptr::NonNull};
+    ///
+    /// /// Counts the bytes handed out to arenas.
+    /// struct Counted(Cell<usize>);
+    ///
+    /// unsafe impl Allocator for &Counted {
+    ///     fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+    ///         self.0.set(self.0.get() + layout.size());
+    ///         Global.allocate(layout)
+    ///     }
+    ///
+    ///     unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+    ///         self.0.set(self.0.get() - layout.size());
+    ///         Global.deallocate(ptr, layout)
+    ///     }
+    /// }
+    ///
+    /// let pool = Counted(Cell::new(0));
+    /// {
+    ///     let arena = Arena::new().with_block_size(256).with_allocator(&pool);
+    ///     arena.alloc(1u32);
+    ///     assert_eq!(256, pool.0.get());
+    /// }
+    /// assert_eq!(0, pool.0.get());
+    /// ```
+    pub fn with_allocator<B: Allocator + Clone>(self, allocator: B) -> Arena<B> {
+        assert!(
+            self.blocks.borrow().is_empty()
+                && self.spare_blocks.borrow().is_empty()
+                && self.regions.borrow().is_empty(),
+            "Cannot change allocator after \
+             blocks have already been allocated"
+        );
+
+        let mut arena = self.new_with_same_settings_in(allocator);
+        arena.hooks = self.hooks;
+        arena
+    }
+
+    /// Build an arena that gets its blocks from `source`, rather than the
+    /// global allocator.
+    ///
+    /// See `BlockSource` for details.  The source is shared with the arena's
+    /// regions.
+    pub fn with_block_source<S: BlockSource + 'static>(self, source: S) -> Arena<A> {
+        Arena {
+            block_source: Some(Arc::new(source)),
+            ..self
+        }
+    }
+
+    /// Build an arena that surrounds oversized allocations with
+    /// inaccessible guard pages, if `enabled`.
+    ///
+    /// Only available on Unix, with the `guard_pages` feature.  This is a
+    /// debugging aid: each oversized allocation is placed in its own
+    /// mapping, flush against a guard page after it, so that writing past
+    /// its end crashes immediately rather than silently corrupting memory.
+    /// There's also a guard page before it, which catches underflows that
+    /// go past the start of its first page.
+    ///
+    /// Allocations with an alignment bigger than a page aren't guarded.
+    /// Guarded allocations are only aligned as much as they need to be, so
+    /// their blocks don't follow `with_block_alignment()`.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let arena = Arena::new().with_guard_pages(true);
+    /// let big = arena.alloc_array(0u8, 10_000);
+    /// big[9_999] = 1;
+    /// // big.as_mut_ptr().add(10_000).write(1) would segfault.
+    /// ```
+    #[cfg(all(unix, feature = "guard_pages"))]
+    pub fn with_guard_pages(self, enabled: bool) -> Arena<A> {
+        let guard_source: Option<Arc<dyn BlockSource>> = if enabled {
+            Some(Arc::new(mmap::GuardedSource))
+        } else {
+            None
+        };
+        Arena {
+            guard_source,
+            ..self
+        }
+    }
+
+    /// Build an arena that calls `hooks` as it allocates.
+    ///
+    /// See `ArenaHooks` for details.  Hooks aren't shared with the arena's
+    /// regions or sub-arenas.
+    pub fn with_hooks<H: ArenaHooks + 'static>(self, hooks: H) -> Arena<A> {
+        Arena {
+            hooks: Some(Box::new(hooks)),
+            ..self
+        }
+    }
+
+    /// Build an arena with room for at least `capacity` bytes of allocations
+    /// already allocated, rather than allocating its first block lazily.
+    ///
+    /// Since this allocates, it should come after any other settings.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let arena = Arena::new().with_block_size(256).with_capacity(4096);
+    /// ```
+    pub fn with_capacity(self, capacity: usize) -> Arena<A> {
+        self.reserve(capacity);
+        self
+    }
+
     //------------------------------------------------------------------------
     // Basic methods
 
     /// Allocates a `T` initialized to `value`
     #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
     pub fn alloc<T: Copy>(&self, value: T) -> &mut T {
         let memory = self.alloc_uninit();
         unsafe {
@@ -185,8 +624,31 @@ impl Arena {
         unsafe { transmute(memory) }
     }
 
+    /// Allocates a `T` initialized to the return value of `f`.
+    ///
+    /// Unlike `alloc()`, this gives the compiler the opportunity to construct
+    /// the value directly in the arena's memory rather than on the stack,
+    /// which matters for very large types.
+    #[inline(always)]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_with<T: Copy, F: FnOnce() -> T>(&self, f: F) -> &mut T {
+        let memory = self.alloc_uninit::<T>();
+        unsafe {
+            memory.as_mut_ptr().write(f());
+        }
+        unsafe { transmute(memory) }
+    }
+
+    /// Allocates a `T` initialized to `T::default()`.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_default<T: Copy + Default>(&self) -> &mut T {
+        self.alloc_with(T::default)
+    }
+
     /// Allocates a `[T]` with all elements initialized to `value`.
     #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
     pub fn alloc_array<T: Copy>(&self, value: T, len: usize) -> &mut [T] {
         let memory = self.alloc_array_uninit(len);
 
@@ -199,281 +661,1999 @@ impl Arena {
         unsafe { transmute(memory) }
     }
 
-    /// Allocates a `[T]` initialized to the contents of `slice`.
+    /// Allocates a `[T; N]` with all elements initialized to `value`.
+    ///
+    /// This is the same as `alloc_array()`, except that the length is known
+    /// at compile time and preserved in the returned type.
     #[inline]
-    pub fn copy_slice<T: Copy>(&self, slice: &[T]) -> &mut [T] {
-        let memory = self.alloc_array_uninit(slice.len());
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_fixed_array<T: Copy, const N: usize>(&self, value: T) -> &mut [T; N] {
+        let memory = self.alloc_array(value, N);
+        unsafe { &mut *(memory.as_mut_ptr() as *mut [T; N]) }
+    }
 
-        for (v, slice_item) in memory.iter_mut().zip(slice.iter()) {
+    /// Allocates a `[T]` with each element initialized to the return value of
+    /// `f` called with that element's index.
+    ///
+    /// If `f` panics, the partially initialized array is simply abandoned in
+    /// the arena, and is never exposed.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_array_with<T: Copy, F: FnMut(usize) -> T>(
+        &self,
+        len: usize,
+        mut f: F,
+    ) -> &mut [T] {
+        let memory = self.alloc_array_uninit::<T>(len);
+
+        for (i, v) in memory.iter_mut().enumerate() {
             unsafe {
-                *v.as_mut_ptr() = *slice_item;
+                v.as_mut_ptr().write(f(i));
             }
         }
 
         unsafe { transmute(memory) }
     }
 
-    /// Allocates a `str` initialized to the contents of `text`.
-    #[inline]
-    pub fn copy_str(&self, text: &str) -> &mut str {
-        let memory = self.alloc_array_uninit::<u8>(text.len());
-
-        for (byte, text_byte) in memory.iter_mut().zip(text.as_bytes().iter()) {
-            unsafe {
-                *byte.as_mut_ptr() = *text_byte;
-            }
-        }
+    /// Like `alloc_array_with()`, but calls `f` for the elements in
+    /// parallel, using rayon's global thread pool.
+    ///
+    /// The array is allocated up front, so only the initialization is
+    /// parallel.
+    #[cfg(feature = "rayon")]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_array_par_with<T, F>(&self, len: usize, f: F) -> &mut [T]
+    where
+        T: Copy + Send,
+        F: Fn(usize) -> T + Sync + Send,
+    {
+        par::fill(self.alloc_array_uninit(len), f)
+    }
 
-        unsafe { std::str::from_utf8_unchecked_mut(transmute(memory)) }
+    /// Allocates a `[T]` with all elements initialized to `T::default()`.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_array_default<T: Copy + Default>(&self, len: usize) -> &mut [T] {
+        self.alloc_array_with(len, |_| T::default())
     }
 
-    //------------------------------------------------------------------------
-    // Initialized allocation methods with alignment.
+    /// Allocates `N` arrays with the lengths given in `lens`, with all
+    /// elements initialized to `value`.
+    ///
+    /// The arrays are carved from a single contiguous allocation, in order.
+    /// This guarantees that they're adjacent in memory, and is cheaper than
+    /// allocating them separately:
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// # let arena = Arena::new();
+    /// let [positions, normals, uvs] = arena.alloc_array_split(0.0f32, [9, 9, 6]);
+    /// assert_eq!(9, positions.len());
+    /// assert_eq!(9, normals.len());
+    /// assert_eq!(6, uvs.len());
+    /// ```
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_array_split<T: Copy, const N: usize>(
+        &self,
+        value: T,
+        lens: [usize; N],
+    ) -> [&mut [T]; N] {
+        let total_len = lens
+            .iter()
+            .try_fold(0usize, |total, len| total.checked_add(*len))
+            .expect("Total array length overflow");
+        let mut rest = self.alloc_array(value, total_len);
+
+        std::array::from_fn(|i| {
+            let (array, tail) = std::mem::take(&mut rest).split_at_mut(lens[i]);
+            rest = tail;
+            array
+        })
+    }
 
-    /// Allocates a `T` initialized to `value`, aligned to at least `align`
-    /// bytes.
+    /// Allocates a header `H` immediately followed by a `[T]` in a single
+    /// contiguous allocation.
+    ///
+    /// The header is initialized to `header`, and all array elements to
+    /// `value`.  Padding is inserted between the two as needed for `T`'s
+    /// alignment, exactly as if they were fields of a `#[repr(C)]` struct.
     #[inline]
-    pub fn alloc_align<T: Copy>(&self, value: T, align: usize) -> &mut T {
-        let memory = self.alloc_align_uninit(align);
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_header_array<H: Copy, T: Copy>(
+        &self,
+        header: H,
+        value: T,
+        len: usize,
+    ) -> (&mut H, &mut [T]) {
+        assert!(
+            size_of::<H>() > 0 && size_of::<T>() > 0,
+            "`Arena` does not support zero-sized types."
+        );
+
+        let (layout, array_offset) = Layout::new::<H>()
+            .extend(Layout::array::<T>(len).unwrap())
+            .unwrap();
+
+        let memory = self.alloc_raw(layout);
         unsafe {
-            *memory.as_mut_ptr() = value;
+            let header_ptr = memory as *mut H;
+            let array_ptr = memory.add(array_offset) as *mut T;
+
+            header_ptr.write(header);
+            for i in 0..len {
+                array_ptr.add(i).write(value);
+            }
+
+            (
+                header_ptr.as_mut().unwrap(),
+                slice::from_raw_parts_mut(array_ptr, len),
+            )
         }
-        unsafe { transmute(memory) }
     }
 
-    /// Allocates a `[T]` with all elements initialized to `value`, aligned to
-    /// at least `align` bytes.
+    /// Allocates a `[T]` initialized to the contents of `slice`.
     #[inline]
-    pub fn alloc_array_align<T: Copy>(&self, value: T, len: usize, align: usize) -> &mut [T] {
-        let memory = self.alloc_array_align_uninit(len, align);
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn copy_slice<T: Copy>(&self, slice: &[T]) -> &mut [T] {
+        let memory = self.alloc_array_uninit(slice.len());
 
-        for v in memory.iter_mut() {
+        for (v, slice_item) in memory.iter_mut().zip(slice.iter()) {
             unsafe {
-                *v.as_mut_ptr() = value;
+                *v.as_mut_ptr() = *slice_item;
             }
         }
 
         unsafe { transmute(memory) }
     }
 
-    /// Allocates a `[T]` initialized to the contents of `slice`, aligned to at
-    /// least `align` bytes.
+    /// Allocates a `[T; N]` initialized to the contents of `array`.
+    ///
+    /// This is the same as `copy_slice()`, except that the length is
+    /// preserved in the returned type.
     #[inline]
-    pub fn copy_slice_align<T: Copy>(&self, slice: &[T], align: usize) -> &mut [T] {
-        let memory = self.alloc_array_align_uninit(slice.len(), align);
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn copy_array<T: Copy, const N: usize>(&self, array: &[T; N]) -> &mut [T; N] {
+        let memory = self.copy_slice(array);
+        unsafe { &mut *(memory.as_mut_ptr() as *mut [T; N]) }
+    }
 
-        for (v, slice_item) in memory.iter_mut().zip(slice.iter()) {
+    /// Allocates a `[T]` initialized to the contents of all of `parts`,
+    /// concatenated in order.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn concat_slices<T: Copy>(&self, parts: &[&[T]]) -> &mut [T] {
+        let len = parts
+            .iter()
+            .try_fold(0usize, |total, part| total.checked_add(part.len()))
+            .expect("Array length overflow");
+        let memory = self.alloc_array_uninit(len);
+
+        let items = parts.iter().flat_map(|part| part.iter());
+        for (v, item) in memory.iter_mut().zip(items) {
             unsafe {
-                *v.as_mut_ptr() = *slice_item;
+                *v.as_mut_ptr() = *item;
             }
         }
 
         unsafe { transmute(memory) }
     }
 
-    //------------------------------------------------------------------------
-    // Uninitialized allocation methods.
-
-    /// Allocates an uninitialized `T`.
+    /// Allocates a `[T]` initialized to the items yielded by `iter`.
+    ///
+    /// If the iterator reports an exact length via its size hint (as all
+    /// `ExactSizeIterator`s should), the array is allocated up-front and
+    /// filled in place.  If the iterator then yields fewer items than it
+    /// reported, the returned array is shortened to match.  If it yields
+    /// more, all of the items are collected as below, and the space
+    /// allocated up-front is wasted.
+    ///
+    /// Otherwise the items are first collected into a temporary heap buffer
+    /// and then copied into the arena, so that the returned array is still
+    /// contiguous.
     #[inline]
-    pub fn alloc_uninit<T: Copy>(&self) -> &mut MaybeUninit<T> {
-        assert!(
-            size_of::<T>() > 0,
-            "`Arena` does not support zero-sized types."
-        );
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_from_iter<T, I>(&self, iter: I) -> &mut [T]
+    where
+        T: Copy,
+        I: IntoIterator<Item = T>,
+    {
+        let mut iter = iter.into_iter();
+        let len = match iter.size_hint() {
+            (lower, Some(upper)) if lower == upper => lower,
+            _ => {
+                let buffer: Vec<T> = iter.collect();
+                return self.copy_slice(&buffer);
+            }
+        };
 
-        let memory = self.alloc_raw(Layout::new::<T>()) as *mut MaybeUninit<T>;
+        let memory = self.alloc_array_uninit::<T>(len);
 
-        unsafe { memory.as_mut().unwrap() }
-    }
+        let mut filled = 0;
+        for (v, item) in memory.iter_mut().zip(&mut iter) {
+            unsafe {
+                v.as_mut_ptr().write(item);
+            }
+            filled += 1;
+        }
+        let memory: &mut [T] = unsafe { transmute(&mut memory[..filled]) };
+
+        // The size hint was wrong, so fall back to collecting everything.
+        if filled == len {
+            if let Some(item) = iter.next() {
+                let mut buffer: Vec<T> = memory.to_vec();
+                buffer.push(item);
+                buffer.extend(iter);
+                return self.copy_slice(&buffer);
+            }
+        }
 
-    /// Allocates a uninitialized `[T]`.
-    #[inline]
-    pub fn alloc_array_uninit<T: Copy>(&self, len: usize) -> &mut [MaybeUninit<T>] {
-        assert!(
-            size_of::<T>() > 0,
-            "`Arena` does not support zero-sized types."
-        );
+        memory
+    }
 
-        let memory = self.alloc_raw(Layout::array::<T>(len).unwrap()) as *mut MaybeUninit<T>;
-        unsafe { slice::from_raw_parts_mut(memory, len) }
+    /// Allocates a `[T]` and moves the contents of `vec` into it.
+    ///
+    /// Unlike `copy_slice()`, this doesn't require `T: Copy`, since the
+    /// elements are moved rather than copied.  The `Vec`'s heap buffer is
+    /// freed, but the elements themselves are never dropped.  See the crate
+    /// docs on non-Copy types.
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_vec<T>(&self, mut vec: Vec<T>) -> &mut [T] {
+        let memory = self.alloc_array_uninit::<T>(vec.len());
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                vec.as_ptr(),
+                memory.as_mut_ptr() as *mut T,
+                memory.len(),
+            );
+            // The elements now belong to the arena.
+            vec.set_len(0);
+            transmute(memory)
+        }
     }
 
-    /// Allocates an uninitialized `T`, aligned to at least `align` bytes.
-    #[inline]
-    pub fn alloc_align_uninit<T: Copy>(&self, align: usize) -> &mut MaybeUninit<T> {
-        assert!(
-            size_of::<T>() > 0,
-            "`Arena` does not support zero-sized types."
-        );
-        assert!(
-            align.is_power_of_two(),
-            "Invalid alignment: not a power of two."
-        );
+    /// Moves the closure `f` into the arena.
+    ///
+    /// The returned reference coerces to `&dyn Fn(...)` or
+    /// `&mut dyn FnMut(...)` as appropriate, which makes it easy to build
+    /// lists of callbacks without boxing each one:
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let arena = Arena::new();
+    /// let offset = 10;
+    /// let callbacks: Vec<&dyn Fn(i32) -> i32> = vec![
+    ///     arena.alloc_closure(|x| x * 2),
+    ///     arena.alloc_closure(move |x| x + offset),
+    /// ];
+    /// assert_eq!(vec![8, 14], callbacks.iter().map(|f| f(4)).collect::<Vec<_>>());
+    /// ```
+    ///
+    /// Unlike the other allocation methods, this accepts zero-sized types,
+    /// since closures that don't capture anything are zero-sized.  Those
+    /// don't take up any space in the arena.
+    ///
+    /// The closure is never dropped, so anything it captures by value is
+    /// leaked.  If that matters, use an `ABox` instead.
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_closure<F>(&self, f: F) -> &mut F {
+        if size_of::<F>() == 0 {
+            let ptr = NonNull::<F>::dangling().as_ptr();
+            unsafe {
+                ptr.write(f);
+                return &mut *ptr;
+            }
+        }
 
-        let memory =
-            self.alloc_raw(Layout::new::<T>().align_to(align).unwrap()) as *mut MaybeUninit<T>;
-        unsafe { memory.as_mut().unwrap() }
+        let memory = self.alloc_uninit::<F>();
+        unsafe {
+            memory.as_mut_ptr().write(f);
+            &mut *memory.as_mut_ptr()
+        }
     }
 
-    /// Allocates a uninitialized `[T]`, aligned to at least `align` bytes.
-    #[inline]
-    pub fn alloc_array_align_uninit<T: Copy>(
+    /// Allocates a `[u8]` initialized to the entire contents of `reader`.
+    ///
+    /// `size_hint` is used as the initial capacity, and should be the expected
+    /// total size in bytes (e.g. the file size) if known, or zero otherwise.
+    /// It isn't required to be accurate, however.
+    ///
+    /// The data is read directly into the arena without an intermediate heap
+    /// buffer.  If an error occurs, the partially read data is left in the
+    /// arena as unused space.
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_from_reader<R: std::io::Read + ?Sized>(
         &self,
-        len: usize,
-        align: usize,
-    ) -> &mut [MaybeUninit<T>] {
+        reader: &mut R,
+        size_hint: usize,
+    ) -> std::io::Result<&mut [u8]> {
+        let mut writer = ArenaWriter::with_capacity_in(size_hint, self);
+        writer.read_to_end(reader)?;
+        Ok(writer.into_slice())
+    }
+
+    /// Allocates the items yielded by `iter` as a series of arrays (chunks),
+    /// filling up the space in each block before moving on to the next.
+    ///
+    /// Unlike `alloc_from_iter()`, the items aren't stored contiguously, but
+    /// in exchange no one-off blocks are created no matter how many items
+    /// there are, and nothing is buffered on the heap.  Useful for large
+    /// amounts of data that only ever need to be iterated over.
+    ///
+    /// Returns the chunks in order.  None of the chunks are empty.
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_iter_chunks<T, I>(&self, iter: I) -> &mut [&mut [T]]
+    where
+        T: Copy,
+        I: IntoIterator<Item = T>,
+    {
         assert!(
             size_of::<T>() > 0,
             "`Arena` does not support zero-sized types."
         );
-        assert!(
-            align.is_power_of_two(),
-            "Invalid alignment: not a power of two."
-        );
 
-        let memory = self.alloc_raw(Layout::array::<T>(len).unwrap().align_to(align).unwrap())
-            as *mut MaybeUninit<T>;
-        unsafe { slice::from_raw_parts_mut(memory, len) }
-    }
+        let mut iter = iter.into_iter().peekable();
+        let mut chunks: Vec<&mut [T]> = Vec::new();
 
-    //------------------------------------------------------------------------
-    // Raw work-horse allocation method.
+        while iter.peek().is_some() {
+            let memory = self.alloc_array_uninit::<T>(self.chunk_capacity::<T>());
+            let capacity = memory.len();
 
-    /// Allocates uninitialized memory with the given layout.
-    ///
-    /// # Safety
-    ///
-    /// Although this function is not itself unsafe, it is very easy to
-    /// accidentally do unsafe things with the returned pointer.
-    ///
-    /// In particular, only memory within the size of the requested layout is
-    /// valid, and the returned allocation is only valid for as long as the
-    /// `Arena` itself is.  The other allocation methods all protect against
-    /// those issues by returning references or slices with appropriate
-    /// lifetimes.
-    pub fn alloc_raw(&self, layout: Layout) -> *mut MaybeUninit<u8> {
-        #[inline(always)]
-        fn alignment_offset(addr: usize, alignment: usize) -> usize {
-            (alignment - (addr % alignment)) % alignment
+            let mut filled = 0;
+            for (v, item) in memory.iter_mut().zip(&mut iter) {
+                unsafe {
+                    v.as_mut_ptr().write(item);
+                }
+                filled += 1;
+            }
+
+            // Give the unused tail of the last chunk back to the arena.
+            self.try_resize_in_place(
+                memory.as_ptr() as *const MaybeUninit<u8>,
+                size_of::<T>() * capacity,
+                size_of::<T>() * filled,
+            );
+
+            chunks.push(unsafe { transmute(&mut memory[..filled]) });
+        }
+
+        // Move the list of chunks into the arena as well.
+        let list =
+            self.alloc_raw(Layout::array::<&mut [T]>(chunks.len()).unwrap()) as *mut &mut [T];
+        let len = chunks.len();
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            unsafe {
+                list.add(i).write(chunk);
+            }
+        }
+
+        unsafe { slice::from_raw_parts_mut(list, len) }
+    }
+
+    /// Allocates a `str` initialized to the contents of `text`.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn copy_str(&self, text: &str) -> &mut str {
+        let memory = self.alloc_array_uninit::<u8>(text.len());
+
+        for (byte, text_byte) in memory.iter_mut().zip(text.as_bytes().iter()) {
+            unsafe {
+                *byte.as_mut_ptr() = *text_byte;
+            }
+        }
+
+        unsafe { std::str::from_utf8_unchecked_mut(transmute(memory)) }
+    }
+
+    /// Allocates a `str` initialized to the characters yielded by `chars`.
+    ///
+    /// The text is built up directly in the arena, without an intermediate
+    /// heap allocation.
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn collect_str<I: IntoIterator<Item = char>>(&self, chars: I) -> &mut str {
+        let mut text = collections::String::new_in(self);
+        text.extend(chars);
+        text.into_str()
+    }
+
+    /// Allocates a `str` initialized to the UTF-8 encoded text in `bytes`,
+    /// replacing invalid sequences with U+FFFD REPLACEMENT CHARACTER.
+    ///
+    /// The replacement is done the same way as `String::from_utf8_lossy()`.
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn copy_str_lossy(&self, bytes: &[u8]) -> &mut str {
+        // Calls `f` on each piece of the decoded text, in order.
+        fn for_each_piece<F: FnMut(&str)>(mut bytes: &[u8], mut f: F) {
+            loop {
+                match std::str::from_utf8(bytes) {
+                    Ok(text) => {
+                        f(text);
+                        return;
+                    }
+                    Err(error) => {
+                        let (valid, rest) = bytes.split_at(error.valid_up_to());
+                        f(unsafe { std::str::from_utf8_unchecked(valid) });
+                        f("\u{FFFD}");
+                        match error.error_len() {
+                            Some(n) => bytes = &rest[n..],
+                            None => return,
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Ok(text) = std::str::from_utf8(bytes) {
+            return self.copy_str(text);
+        }
+
+        let mut len = 0;
+        for_each_piece(bytes, |piece| len += piece.len());
+
+        let memory = self.alloc_array_uninit::<u8>(len);
+        let mut i = 0;
+        for_each_piece(bytes, |piece| {
+            for (byte, text_byte) in memory[i..(i + piece.len())]
+                .iter_mut()
+                .zip(piece.as_bytes().iter())
+            {
+                unsafe {
+                    *byte.as_mut_ptr() = *text_byte;
+                }
+            }
+            i += piece.len();
+        });
+
+        unsafe { std::str::from_utf8_unchecked_mut(transmute(memory)) }
+    }
+
+    /// Allocates a `str` initialized to the UTF-16 encoded text in `text`.
+    ///
+    /// Returns an error if `text` contains unpaired surrogates.
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn copy_str_from_utf16(&self, text: &[u16]) -> Result<&mut str, DecodeUtf16Error> {
+        let mut len = 0;
+        for c in char::decode_utf16(text.iter().copied()) {
+            len += c?.len_utf8();
+        }
+
+        Ok(self.write_str_from_chars(
+            len,
+            char::decode_utf16(text.iter().copied()).map(|c| c.unwrap()),
+        ))
+    }
+
+    /// Allocates a `str` initialized to the UTF-16 encoded text in `text`,
+    /// replacing unpaired surrogates with U+FFFD REPLACEMENT CHARACTER.
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn copy_str_from_utf16_lossy(&self, text: &[u16]) -> &mut str {
+        let chars = || {
+            char::decode_utf16(text.iter().copied())
+                .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        };
+        let len = chars().map(|c| c.len_utf8()).sum();
+
+        self.write_str_from_chars(len, chars())
+    }
+
+    /// Allocates a `str` of `len` bytes, initialized by encoding `chars` as
+    /// UTF-8.  `chars` must encode to exactly `len` bytes.
+    #[cfg_attr(feature = "instrument", track_caller)]
+    fn write_str_from_chars<I: Iterator<Item = char>>(&self, len: usize, chars: I) -> &mut str {
+        let memory = self.alloc_array_uninit::<u8>(len);
+
+        let mut i = 0;
+        for c in chars {
+            let mut buf = [0u8; 4];
+            for byte in c.encode_utf8(&mut buf).as_bytes() {
+                unsafe {
+                    *memory[i].as_mut_ptr() = *byte;
+                }
+                i += 1;
+            }
+        }
+        assert!(i == len, "Character data was shorter than expected");
+
+        unsafe { std::str::from_utf8_unchecked_mut(transmute(memory)) }
+    }
+
+    /// Allocates a `CStr` initialized to the contents of `text`, including
+    /// its nul terminator.
+    ///
+    /// Unlike the other copy methods this returns a shared reference, since
+    /// `CStr` has no API for mutation anyway.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn copy_cstr(&self, text: &CStr) -> &CStr {
+        let memory = self.copy_slice(text.to_bytes_with_nul());
+        unsafe { CStr::from_bytes_with_nul_unchecked(memory) }
+    }
+
+    /// Allocates a `BStr` initialized to the contents of `text`.
+    ///
+    /// Only available with the `bstr` feature.  Note that arena-allocated
+    /// `[u8]`s can also be viewed as `BStr`s via bstr's `AsMut<BStr>` and
+    /// `ByteSlice` impls.
+    #[cfg(feature = "bstr")]
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn copy_bstr(&self, text: &bstr::BStr) -> &mut bstr::BStr {
+        self.copy_slice(text.as_ref()).as_mut()
+    }
+
+    /// Allocates a `str` initialized to the contents of all of `parts`,
+    /// concatenated in order.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn concat_strs(&self, parts: &[&str]) -> &mut str {
+        let len = parts
+            .iter()
+            .try_fold(0usize, |total, part| total.checked_add(part.len()))
+            .expect("String length overflow");
+        let memory = self.alloc_array_uninit::<u8>(len);
+
+        let bytes = parts.iter().flat_map(|part| part.as_bytes().iter());
+        for (byte, text_byte) in memory.iter_mut().zip(bytes) {
+            unsafe {
+                *byte.as_mut_ptr() = *text_byte;
+            }
+        }
+
+        unsafe { std::str::from_utf8_unchecked_mut(transmute(memory)) }
+    }
+
+    /// Allocates a `str` initialized to the contents of all of `parts`,
+    /// concatenated in order with `separator` between each of them.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn join_strs(&self, parts: &[&str], separator: &str) -> &mut str {
+        let len = parts
+            .iter()
+            .try_fold(0usize, |total, part| total.checked_add(part.len()))
+            .and_then(|len| {
+                separator
+                    .len()
+                    .checked_mul(parts.len().saturating_sub(1))?
+                    .checked_add(len)
+            })
+            .expect("String length overflow");
+        let memory = self.alloc_array_uninit::<u8>(len);
+
+        let bytes = parts.iter().enumerate().flat_map(|(i, part)| {
+            let sep = if i == 0 { "" } else { separator };
+            sep.as_bytes().iter().chain(part.as_bytes().iter())
+        });
+        for (byte, text_byte) in memory.iter_mut().zip(bytes) {
+            unsafe {
+                *byte.as_mut_ptr() = *text_byte;
+            }
+        }
+
+        unsafe { std::str::from_utf8_unchecked_mut(transmute(memory)) }
+    }
+
+    /// Allocates a `str` initialized to the formatted output of `args`.
+    ///
+    /// Typically used with `format_args!()`, like so:
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// # let arena = Arena::new();
+    /// let text = arena.alloc_fmt(format_args!("{} + {} = {}", 1, 2, 1 + 2));
+    /// assert_eq!("1 + 2 = 3", text);
+    /// ```
+    ///
+    /// This formats directly into the arena without any intermediate heap
+    /// allocations.  To do that, the formatting is done twice: once to measure
+    /// the length of the output, and once to actually write it.
+    ///
+    /// Panics if a formatting trait implementation returns an error or
+    /// produces different output lengths between the two passes.
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_fmt(&self, args: fmt::Arguments) -> &mut str {
+        struct Counter(usize);
+        impl fmt::Write for Counter {
+            fn write_str(&mut self, text: &str) -> fmt::Result {
+                self.0 += text.len();
+                Ok(())
+            }
+        }
+
+        struct Writer<'a>(&'a mut [MaybeUninit<u8>], usize);
+        impl<'a> fmt::Write for Writer<'a> {
+            fn write_str(&mut self, text: &str) -> fmt::Result {
+                let dest = self
+                    .0
+                    .get_mut(self.1..(self.1 + text.len()))
+                    .ok_or(fmt::Error)?;
+                for (byte, text_byte) in dest.iter_mut().zip(text.as_bytes().iter()) {
+                    unsafe {
+                        *byte.as_mut_ptr() = *text_byte;
+                    }
+                }
+                self.1 += text.len();
+                Ok(())
+            }
+        }
+
+        if let Some(text) = args.as_str() {
+            return self.copy_str(text);
+        }
+
+        let mut counter = Counter(0);
+        fmt::write(&mut counter, args)
+            .expect("A formatting trait implementation returned an error");
+
+        let mut writer = Writer(self.alloc_array_uninit::<u8>(counter.0), 0);
+        let result = fmt::write(&mut writer, args);
+        assert!(
+            result.is_ok() && writer.1 == counter.0,
+            "Formatting produced inconsistent output"
+        );
+
+        unsafe { std::str::from_utf8_unchecked_mut(transmute(writer.0)) }
+    }
+
+    //------------------------------------------------------------------------
+    // Clone-based allocation methods.
+
+    /// Allocates a `T` initialized to a clone of `value`.
+    ///
+    /// The clone is never dropped.  See the crate docs on non-Copy types.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_clone<T: Clone>(&self, value: &T) -> &mut T {
+        let memory = self.alloc_uninit::<T>();
+        unsafe {
+            memory.as_mut_ptr().write(value.clone());
+            &mut *memory.as_mut_ptr()
+        }
+    }
+
+    /// Allocates a `[T]` initialized to clones of the contents of `slice`.
+    ///
+    /// The clones are never dropped.  See the crate docs on non-Copy types.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn copy_slice_clone<T: Clone>(&self, slice: &[T]) -> &mut [T] {
+        let memory = self.alloc_array_uninit::<T>(slice.len());
+
+        for (v, slice_item) in memory.iter_mut().zip(slice.iter()) {
+            unsafe {
+                v.as_mut_ptr().write(slice_item.clone());
+            }
+        }
+
+        unsafe { slice::from_raw_parts_mut(memory.as_mut_ptr() as *mut T, slice.len()) }
+    }
+
+    //------------------------------------------------------------------------
+    // Drop-aware allocation methods.
+
+    /// Allocates a `T` initialized to `value`, which is dropped when the arena
+    /// is cleared or dropped.
+    ///
+    /// Values are dropped in the reverse order they were allocated.
+    ///
+    /// `T` must be `'static` because the value may be dropped long after
+    /// anything it borrows has gone away, and must be `Send` because the arena
+    /// itself is.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let arena = Arena::new();
+    /// let names = arena.alloc_droppable(vec!["Alice".to_string()]);
+    /// names.push("Bob".into());
+    /// assert_eq!(2, names.len());
+    /// ```
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_droppable<T: Send + 'static>(&self, value: T) -> &mut T {
+        let memory = self.alloc_uninit::<T>();
+        let ptr = memory.as_mut_ptr();
+        unsafe {
+            ptr.write(value);
+            if std::mem::needs_drop::<T>() {
+                let node = self.alloc_raw(DropList::node_layout());
+                self.drop_list.push(node, ptr);
+            }
+            &mut *ptr
+        }
+    }
+
+    /// Allocates a pinned `T` initialized to `value`, which is dropped when
+    /// the arena is cleared or dropped.
+    ///
+    /// Arena allocations never move, so they can be pinned for free.  The
+    /// value is guaranteed to stay at the same address, and to be dropped
+    /// before its memory is freed or reused, as required by `Pin`.  This makes
+    /// it suitable for self-referential and intrusive data structures.
+    ///
+    /// Like `alloc_droppable()`, this requires `T: Send + 'static`.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_pinned<T: Send + 'static>(&self, value: T) -> std::pin::Pin<&mut T> {
+        // Safe because the value never moves, and is dropped before its
+        // memory is invalidated.
+        unsafe { std::pin::Pin::new_unchecked(self.alloc_droppable(value)) }
+    }
+
+    /// Allocates a pinned, uninitialized `T`.
+    ///
+    /// As with the other uninitialized allocation methods, the arena never
+    /// drops anything written into this.  To uphold the `Pin` guarantees
+    /// for a `T` that has a destructor, the value must be dropped manually
+    /// before the arena is cleared or dropped.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_pinned_uninit<T>(&self) -> std::pin::Pin<&mut MaybeUninit<T>> {
+        // Safe because `MaybeUninit` has no destructor, and the memory never
+        // moves.
+        unsafe { std::pin::Pin::new_unchecked(self.alloc_uninit::<T>()) }
+    }
+
+    //------------------------------------------------------------------------
+    // Stack-discipline allocation methods.
+
+    /// Moves `value` into the arena, and returns a box that reclaims its
+    /// memory when dropped, if it's the most recent allocation.
+    ///
+    /// See `StackBox` for details.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn push<T>(&self, value: T) -> StackBox<'_, T, A> {
+        StackBox::new_in(value, self)
+    }
+
+    /// Gives the memory of `value` back to the arena, if it's the most recent
+    /// allocation.  Returns whether it was reclaimed.
+    ///
+    /// Nothing is dropped.
+    ///
+    /// # Safety
+    ///
+    /// `value` must have been allocated from this arena, and must not be used
+    /// after this call (including via any other references to it).
+    pub unsafe fn pop_last<T: ?Sized>(&self, value: &mut T) -> bool {
+        self.try_resize_in_place(
+            value as *mut T as *const MaybeUninit<u8>,
+            size_of_val(value),
+            0,
+        )
+    }
+
+    //------------------------------------------------------------------------
+    // Handle-based allocation methods.
+
+    /// Allocates a `T` initialized to `value`, and returns a `Handle` to it
+    /// rather than a reference.
+    ///
+    /// The value can be accessed with `get()` and `get_mut()` until the arena
+    /// is cleared (or the value is rolled back by `scope()` or `temp()`),
+    /// after which they return `None`.  See `Handle` for details.
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_handle<T: Copy + Send + 'static>(&self, value: T) -> Handle<T> {
+        let memory = self.alloc_uninit::<T>();
+        memory.write(value);
+        self.handles.insert(memory.as_mut_ptr())
+    }
+
+    /// The value for `handle`, or `None` if it's no longer valid.
+    #[inline]
+    pub fn get<T: Copy + Send + 'static>(&self, handle: Handle<T>) -> Option<&T> {
+        self.handles.get(handle).map(|ptr| unsafe { &*ptr })
+    }
+
+    /// The value for `handle` as a mutable reference, or `None` if it's no
+    /// longer valid.
+    #[inline]
+    pub fn get_mut<T: Copy + Send + 'static>(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        self.handles.get(handle).map(|ptr| unsafe { &mut *ptr })
+    }
+
+    //------------------------------------------------------------------------
+    // Initialized allocation methods with alignment.
+
+    /// Allocates a `T` initialized to `value`, aligned to at least `align`
+    /// bytes.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_align<T: Copy>(&self, value: T, align: usize) -> &mut T {
+        let memory = self.alloc_align_uninit(align);
+        unsafe {
+            *memory.as_mut_ptr() = value;
+        }
+        unsafe { transmute(memory) }
+    }
+
+    /// Allocates a `[T]` with all elements initialized to `value`, aligned to
+    /// at least `align` bytes.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_array_align<T: Copy>(&self, value: T, len: usize, align: usize) -> &mut [T] {
+        let memory = self.alloc_array_align_uninit(len, align);
+
+        for v in memory.iter_mut() {
+            unsafe {
+                *v.as_mut_ptr() = value;
+            }
+        }
+
+        unsafe { transmute(memory) }
+    }
+
+    /// Allocates a `[T]` initialized to the contents of `slice`, aligned to at
+    /// least `align` bytes.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn copy_slice_align<T: Copy>(&self, slice: &[T], align: usize) -> &mut [T] {
+        let memory = self.alloc_array_align_uninit(slice.len(), align);
+
+        for (v, slice_item) in memory.iter_mut().zip(slice.iter()) {
+            unsafe {
+                *v.as_mut_ptr() = *slice_item;
+            }
+        }
+
+        unsafe { transmute(memory) }
+    }
+
+    /// Allocates a `T` initialized to `value`, aligned to at least the cache
+    /// line size.
+    ///
+    /// Equivalent to `alloc_align(value, CACHE_LINE)`.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_cacheline<T: Copy>(&self, value: T) -> &mut T {
+        self.alloc_align(value, CACHE_LINE)
+    }
+
+    /// Allocates a `[T]` with all elements initialized to `value`, aligned to
+    /// at least the cache line size.
+    ///
+    /// Equivalent to `alloc_array_align(value, len, CACHE_LINE)`.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_array_cacheline<T: Copy>(&self, value: T, len: usize) -> &mut [T] {
+        self.alloc_array_align(value, len, CACHE_LINE)
+    }
+
+    /// Allocates an array of `len` elements initialized to `value`, with
+    /// _every_ element aligned to at least `align` bytes.
+    ///
+    /// Unlike the other aligned array methods, this pads the space between
+    /// elements as needed, so the result can't be a plain `[T]`.  Instead, a
+    /// `StridedArray` view is returned.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_array_strided<T: Copy>(
+        &self,
+        value: T,
+        len: usize,
+        align: usize,
+    ) -> StridedArray<'_, T> {
+        assert!(
+            size_of::<T>() > 0,
+            "`Arena` does not support zero-sized types."
+        );
+        assert!(
+            align.is_power_of_two(),
+            "Invalid alignment: not a power of two."
+        );
+
+        let element_layout = Layout::new::<T>().align_to(align).unwrap().pad_to_align();
+        let stride = element_layout.size();
+        let layout = Layout::from_size_align(
+            stride.checked_mul(len).expect("Array size overflow"),
+            element_layout.align(),
+        )
+        .unwrap();
+
+        let memory = self.alloc_raw(layout);
+        for i in 0..len {
+            unsafe {
+                (memory.add(i * stride) as *mut T).write(value);
+            }
+        }
+
+        unsafe { StridedArray::from_raw_parts(memory as *mut T, len, stride) }
+    }
+
+    /// Allocates a 2D array of `width` by `height` elements initialized to
+    /// `value`, with each row aligned to at least `row_align` bytes.
+    ///
+    /// Rows are padded as needed to keep them aligned, like the row pitch of
+    /// GPU images.  The result is returned as an `Array2d` view, indexable by
+    /// `(x, y)`.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_array_2d<T: Copy>(
+        &self,
+        value: T,
+        width: usize,
+        height: usize,
+        row_align: usize,
+    ) -> Array2d<'_, T> {
+        assert!(
+            size_of::<T>() > 0,
+            "`Arena` does not support zero-sized types."
+        );
+        assert!(
+            row_align.is_power_of_two(),
+            "Invalid alignment: not a power of two."
+        );
+
+        let row_layout = Layout::array::<T>(width)
+            .unwrap()
+            .align_to(row_align)
+            .unwrap()
+            .pad_to_align();
+        let pitch = row_layout.size();
+        let layout = Layout::from_size_align(
+            pitch.checked_mul(height).expect("Array size overflow"),
+            row_layout.align(),
+        )
+        .unwrap();
+
+        let memory = self.alloc_raw(layout);
+        for y in 0..height {
+            for x in 0..width {
+                unsafe {
+                    (memory.add(y * pitch) as *mut T).add(x).write(value);
+                }
+            }
+        }
+
+        unsafe { Array2d::from_raw_parts(memory as *mut T, width, height, pitch) }
+    }
+
+    //------------------------------------------------------------------------
+    // Uninitialized allocation methods.
+    //
+    // These don't require `T: Copy`, since they never touch any `T`s
+    // themselves.  But keep in mind that the arena never runs `Drop` on
+    // anything, so anything with a destructor that's written into these
+    // allocations is leaked.
+
+    /// Allocates an uninitialized `T`.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_uninit<T>(&self) -> &mut MaybeUninit<T> {
+        assert!(
+            size_of::<T>() > 0,
+            "`Arena` does not support zero-sized types."
+        );
+
+        let memory = self.alloc_raw(Layout::new::<T>()) as *mut MaybeUninit<T>;
+
+        unsafe { memory.as_mut().unwrap() }
+    }
+
+    /// Allocates a uninitialized `[T]`.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_array_uninit<T>(&self, len: usize) -> &mut [MaybeUninit<T>] {
+        assert!(
+            size_of::<T>() > 0,
+            "`Arena` does not support zero-sized types."
+        );
+
+        let memory = self.alloc_raw(Layout::array::<T>(len).unwrap()) as *mut MaybeUninit<T>;
+        unsafe { slice::from_raw_parts_mut(memory, len) }
+    }
+
+    /// Allocates an uninitialized `T`, aligned to at least `align` bytes.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_align_uninit<T>(&self, align: usize) -> &mut MaybeUninit<T> {
+        assert!(
+            size_of::<T>() > 0,
+            "`Arena` does not support zero-sized types."
+        );
+        assert!(
+            align.is_power_of_two(),
+            "Invalid alignment: not a power of two."
+        );
+
+        let memory =
+            self.alloc_raw(Layout::new::<T>().align_to(align).unwrap()) as *mut MaybeUninit<T>;
+        unsafe { memory.as_mut().unwrap() }
+    }
+
+    /// Allocates a uninitialized `[T]`, aligned to at least `align` bytes.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_array_align_uninit<T>(&self, len: usize, align: usize) -> &mut [MaybeUninit<T>] {
+        assert!(
+            size_of::<T>() > 0,
+            "`Arena` does not support zero-sized types."
+        );
+        assert!(
+            align.is_power_of_two(),
+            "Invalid alignment: not a power of two."
+        );
+
+        let memory = self.alloc_raw(Layout::array::<T>(len).unwrap().align_to(align).unwrap())
+            as *mut MaybeUninit<T>;
+        unsafe { slice::from_raw_parts_mut(memory, len) }
+    }
+
+    /// Allocates uninitialized memory with the given layout, as a
+    /// `[MaybeUninit<u8>]` of exactly `layout.size()` bytes.
+    ///
+    /// This is a safe alternative to `alloc_raw()` for building custom
+    /// layouts, with the arena's lifetime attached to the result.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_layout(&self, layout: Layout) -> &mut [MaybeUninit<u8>] {
+        let memory = self.alloc_raw(layout);
+        unsafe { slice::from_raw_parts_mut(memory, layout.size()) }
+    }
+
+    //------------------------------------------------------------------------
+    // Zero-initialized allocation methods.
+
+    /// Allocates a `T` with all of its bytes set to zero.
+    ///
+    /// # Safety
+    ///
+    /// The all-zero bit pattern must be a valid value of `T`.  This is true of
+    /// e.g. integers, floats, and arrays/structs made up of only those, but
+    /// is _not_ true of e.g. references, `NonZero*` types, and many enums.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub unsafe fn alloc_zeroed<T: Copy>(&self) -> &mut T {
+        let memory = self.alloc_uninit::<T>();
+        memory.as_mut_ptr().write_bytes(0, 1);
+        transmute(memory)
+    }
+
+    /// Allocates a `[T]` with all of its bytes set to zero.
+    ///
+    /// This zeroes the whole array in one go, and is therefore typically
+    /// much faster than `alloc_array()` for large arrays.
+    ///
+    /// # Safety
+    ///
+    /// The all-zero bit pattern must be a valid value of `T`.  See
+    /// `alloc_zeroed()` for details.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub unsafe fn alloc_array_zeroed<T: Copy>(&self, len: usize) -> &mut [T] {
+        let memory = self.alloc_array_uninit::<T>(len);
+        memory.as_mut_ptr().write_bytes(0, len);
+        transmute(memory)
+    }
+
+    //------------------------------------------------------------------------
+    // Byte reinterpreting allocation methods.
+
+    /// Allocates a `[T]` initialized by reinterpreting the contents of
+    /// `bytes`, which need not be aligned.
+    ///
+    /// This is useful for e.g. parsing binary formats straight from a file
+    /// mapping, where the data may be at arbitrary byte offsets.  The
+    /// returned array is properly aligned for `T`.
+    ///
+    /// Panics if the length of `bytes` isn't a multiple of the size of `T`.
+    ///
+    /// # Safety
+    ///
+    /// Every chunk of `size_of::<T>()` bytes in `bytes` must be a valid bit
+    /// pattern for a `T`.  This is true for any bytes if e.g. `T` is an
+    /// integer or float type, or an array/struct of those without padding.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub unsafe fn copy_unaligned<T: Copy>(&self, bytes: &[u8]) -> &mut [T] {
+        assert!(
+            size_of::<T>() > 0,
+            "`Arena` does not support zero-sized types."
+        );
+        assert!(
+            bytes.len() % size_of::<T>() == 0,
+            "Byte length is not a multiple of the type's size."
+        );
+
+        let len = bytes.len() / size_of::<T>();
+        let memory = self.alloc_array_uninit::<T>(len);
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), memory.as_mut_ptr() as *mut u8, bytes.len());
+        transmute(memory)
+    }
+
+    /// Allocates a `T` with all of its bytes set to zero.
+    ///
+    /// A safe version of `alloc_zeroed()` for types that implement
+    /// bytemuck's `Zeroable` trait.  Only available with the `bytemuck`
+    /// feature.
+    #[cfg(feature = "bytemuck")]
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_zeroable<T: Copy + bytemuck::Zeroable>(&self) -> &mut T {
+        unsafe { self.alloc_zeroed() }
+    }
+
+    /// Allocates a `[T]` with all of its bytes set to zero.
+    ///
+    /// A safe version of `alloc_array_zeroed()` for types that implement
+    /// bytemuck's `Zeroable` trait.  Only available with the `bytemuck`
+    /// feature.
+    #[cfg(feature = "bytemuck")]
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_array_zeroable<T: Copy + bytemuck::Zeroable>(&self, len: usize) -> &mut [T] {
+        unsafe { self.alloc_array_zeroed(len) }
+    }
+
+    /// Allocates a `[T]` initialized by reinterpreting the contents of
+    /// `bytes`, which need not be aligned.
+    ///
+    /// A safe version of `copy_unaligned()` for types that implement
+    /// bytemuck's `Pod` trait.  Only available with the `bytemuck` feature.
+    ///
+    /// Panics if the length of `bytes` isn't a multiple of the size of `T`.
+    #[cfg(feature = "bytemuck")]
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn copy_pod_from_bytes<T: bytemuck::Pod>(&self, bytes: &[u8]) -> &mut [T] {
+        unsafe { self.copy_unaligned(bytes) }
+    }
+
+    /// Allocates a `[U]` initialized by reinterpreting the contents of
+    /// `slice`.
+    ///
+    /// Unlike `bytemuck::cast_slice()`, this works regardless of the
+    /// alignment of `slice`, since the data is copied into properly aligned
+    /// arena memory.  Only available with the `bytemuck` feature.
+    ///
+    /// Panics if the size in bytes of `slice` isn't a multiple of the size of
+    /// `U`.
+    #[cfg(feature = "bytemuck")]
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn copy_cast_slice<T: bytemuck::Pod, U: bytemuck::Pod>(&self, slice: &[T]) -> &mut [U] {
+        self.copy_pod_from_bytes(bytemuck::cast_slice(slice))
+    }
+
+    /// Allocates a `T` initialized by reinterpreting `bytes`, which need not
+    /// be aligned.
+    ///
+    /// Returns `None` if the length of `bytes` isn't exactly the size of `T`.
+    /// Only available with the `zerocopy` feature.
+    #[cfg(feature = "zerocopy")]
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn copy_from_bytes<T: Copy + zerocopy::FromBytes>(&self, bytes: &[u8]) -> Option<&mut T> {
+        if bytes.len() != size_of::<T>() {
+            return None;
+        }
+
+        let memory = self.alloc_uninit::<T>();
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                bytes.as_ptr(),
+                memory.as_mut_ptr() as *mut u8,
+                bytes.len(),
+            );
+            Some(transmute(memory))
+        }
+    }
+
+    /// Allocates a `T` initialized by reinterpreting the start of `bytes`,
+    /// which need not be aligned, and returns it along with the remaining
+    /// bytes.
+    ///
+    /// Returns `None` if `bytes` is shorter than the size of `T`.  Only
+    /// available with the `zerocopy` feature.
+    #[cfg(feature = "zerocopy")]
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn copy_from_prefix<'a, 'b, T: Copy + zerocopy::FromBytes>(
+        &'a self,
+        bytes: &'b [u8],
+    ) -> Option<(&'a mut T, &'b [u8])> {
+        if bytes.len() < size_of::<T>() {
+            return None;
+        }
+
+        let (head, rest) = bytes.split_at(size_of::<T>());
+        self.copy_from_bytes(head).map(|value| (value, rest))
+    }
+
+    /// Allocates a `[T]` initialized by reinterpreting `bytes`, which need
+    /// not be aligned.
+    ///
+    /// Returns `None` if the length of `bytes` isn't a multiple of the size
+    /// of `T`.  Only available with the `zerocopy` feature.
+    #[cfg(feature = "zerocopy")]
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn copy_slice_from_bytes<T: Copy + zerocopy::FromBytes>(
+        &self,
+        bytes: &[u8],
+    ) -> Option<&mut [T]> {
+        if size_of::<T>() == 0 || bytes.len() % size_of::<T>() != 0 {
+            return None;
+        }
+
+        Some(unsafe { self.copy_unaligned(bytes) })
+    }
+
+    //------------------------------------------------------------------------
+    // Fallible allocation methods.
+    //
+    // These return an error instead of aborting when the arena needs a new
+    // block and it can't be allocated.
+
+    /// Same as `alloc()`, but returns an error if memory can't be allocated.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn try_alloc<T: Copy>(&self, value: T) -> Result<&mut T, AllocError> {
+        let memory = self.try_alloc_uninit()?;
+        unsafe {
+            *memory.as_mut_ptr() = value;
+        }
+        Ok(unsafe { transmute(memory) })
+    }
+
+    /// Same as `alloc_array()`, but returns an error if memory can't be
+    /// allocated.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn try_alloc_array<T: Copy>(&self, value: T, len: usize) -> Result<&mut [T], AllocError> {
+        let memory = self.try_alloc_array_uninit(len)?;
+
+        for v in memory.iter_mut() {
+            unsafe {
+                *v.as_mut_ptr() = value;
+            }
+        }
+
+        Ok(unsafe { transmute(memory) })
+    }
+
+    /// Same as `copy_slice()`, but returns an error if memory can't be
+    /// allocated.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn try_copy_slice<T: Copy>(&self, slice: &[T]) -> Result<&mut [T], AllocError> {
+        let memory = self.try_alloc_array_uninit(slice.len())?;
+
+        for (v, slice_item) in memory.iter_mut().zip(slice.iter()) {
+            unsafe {
+                *v.as_mut_ptr() = *slice_item;
+            }
+        }
+
+        Ok(unsafe { transmute(memory) })
+    }
+
+    /// Same as `copy_str()`, but returns an error if memory can't be
+    /// allocated.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn try_copy_str(&self, text: &str) -> Result<&mut str, AllocError> {
+        let memory = self.try_copy_slice(text.as_bytes())?;
+        Ok(unsafe { std::str::from_utf8_unchecked_mut(memory) })
+    }
+
+    /// Same as `alloc_uninit()`, but returns an error if memory can't be
+    /// allocated.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn try_alloc_uninit<T>(&self) -> Result<&mut MaybeUninit<T>, AllocError> {
+        assert!(
+            size_of::<T>() > 0,
+            "`Arena` does not support zero-sized types."
+        );
+
+        let memory = self.alloc_raw_impl(Layout::new::<T>())? as *mut MaybeUninit<T>;
+        Ok(unsafe { memory.as_mut().unwrap() })
+    }
+
+    /// Same as `alloc_array_uninit()`, but returns an error if memory can't
+    /// be allocated, including if the array would be too large to
+    /// represent.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn try_alloc_array_uninit<T>(
+        &self,
+        len: usize,
+    ) -> Result<&mut [MaybeUninit<T>], AllocError> {
+        assert!(
+            size_of::<T>() > 0,
+            "`Arena` does not support zero-sized types."
+        );
+
+        let layout = Layout::array::<T>(len).map_err(|_| ArenaAllocError::LayoutOverflow)?;
+        let memory = self.alloc_raw_impl(layout)? as *mut MaybeUninit<T>;
+        Ok(unsafe { slice::from_raw_parts_mut(memory, len) })
+    }
+
+    /// Same as `alloc_layout()`, but returns an error if memory can't be
+    /// allocated.
+    #[inline]
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn try_alloc_layout(&self, layout: Layout) -> Result<&mut [MaybeUninit<u8>], AllocError> {
+        let memory = self.alloc_raw_impl(layout)?;
+        Ok(unsafe { slice::from_raw_parts_mut(memory, layout.size()) })
+    }
+
+    //------------------------------------------------------------------------
+    // Raw work-horse allocation method.
+
+    /// Allocates uninitialized memory with the given layout.
+    ///
+    /// # Safety
+    ///
+    /// Although this function is not itself unsafe, it is very easy to
+    /// accidentally do unsafe things with the returned pointer.
+    ///
+    /// In particular, only memory within the size of the requested layout is
+    /// valid, and the returned allocation is only valid for as long as the
+    /// `Arena` itself is.  The other allocation methods all protect against
+    /// those issues by returning references or slices with appropriate
+    /// lifetimes.
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_raw(&self, layout: Layout) -> *mut MaybeUninit<u8> {
+        match self.alloc_raw_impl(layout) {
+            Ok(memory) => memory,
+            Err(error) => self.alloc_failed(error),
+        }
+    }
+
+    /// Same as `alloc_raw()`, but returns an error if memory can't be
+    /// allocated, rather than aborting.
+    ///
+    /// This is the fallible entry point for building other allocation APIs
+    /// on top of the arena, and the same safety caveats as `alloc_raw()`
+    /// apply.  The error distinguishes between sizes too large to represent
+    /// and the backing allocator running out of memory.
+    ///
+    /// ```rust
+    /// # use kioku::{Arena, ArenaAllocError};
+    /// # use std::alloc::Layout;
+    /// let arena = Arena::new();
+    /// let ptr = arena.try_alloc_raw(Layout::new::<u64>()).unwrap();
+    /// assert_eq!(0, ptr.as_ptr() as usize % 8);
+    ///
+    /// let huge = Layout::from_size_align(isize::MAX as usize / 2, 8).unwrap();
+    /// assert!(matches!(
+    ///     arena.try_alloc_raw(huge),
+    ///     Err(ArenaAllocError::OutOfMemory(_))
+    /// ));
+    /// ```
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn try_alloc_raw(&self, layout: Layout) -> Result<NonNull<u8>, ArenaAllocError> {
+        let memory = self.alloc_raw_impl(layout)?;
+        Ok(unsafe { NonNull::new_unchecked(memory as *mut u8) })
+    }
+
+    /// The implementation of `alloc_raw()`, which returns an error if a new
+    /// block is needed and can't be allocated.
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub(crate) fn alloc_raw_impl(
+        &self,
+        layout: Layout,
+    ) -> Result<*mut MaybeUninit<u8>, ArenaAllocError> {
+        let alignment = layout.align();
+        let size = layout.size();
+
+        self.stat_alloc_count
+            .set(self.stat_alloc_count.get().saturating_add(1));
+        self.stat_total_alloc_count
+            .set(self.stat_total_alloc_count.get().saturating_add(1));
+        self.call_hooks(|hooks| hooks.on_alloc(layout));
+        #[cfg(feature = "instrument")]
+        {
+            // The records outlive any `global::with_arena()` scope, so they
+            // mustn't be allocated in one.
+            let location = std::panic::Location::caller();
+            global::unscoped(|| {
+                self.instrumentation
+                    .borrow_mut()
+                    .record(size, alignment, location)
+            });
+        }
+
+        let mut blocks = self.blocks.borrow_mut();
+
+        // Add the first block if we're empty.
+        if blocks.is_empty() {
+            let block =
+                self.try_new_block(&blocks, self.min_block_size, self.block_alignment, false)?;
+            blocks.push_front(block);
+        }
+
+        // If we're zero-sized, just put us at the start of the current block,
+        // or at a dangling pointer if that isn't aligned enough.
+        if size == 0 {
+            let ptr = blocks.front_mut().unwrap().as_mut_ptr();
+            if alignment_offset(ptr as usize, alignment) == 0 {
+                return Ok(ptr);
+            }
+            return Ok(alignment as *mut MaybeUninit<u8>);
+        }
+
+        // Find our starting index for if we're allocating in the current block.
+        let start_index_proposal = {
+            let cur_block = blocks.front().unwrap();
+            let block_addr = cur_block.as_ptr() as usize;
+            let block_filled = cur_block.len();
+            block_filled + alignment_offset(block_addr + block_filled, alignment)
+        };
+
+        // If it will fit in the current block, use the current block.
+        if (start_index_proposal + size) <= blocks.front().unwrap().capacity() {
+            let block_count = blocks.len();
+            let cur_block = blocks.front_mut().unwrap();
+
+            // Do the bump allocation.
+            let new_len = (start_index_proposal + size).max(cur_block.len());
+            unsafe { cur_block.set_len(new_len) };
+
+            // Update stats.
+            self.stat_space_allocated
+                .set(self.stat_space_allocated.get() + size);
+            self.update_peak_stats(block_count);
+
+            // Return the allocation.
+            Ok(unsafe { cur_block.as_mut_ptr().add(start_index_proposal) })
+        }
+        // If it won't fit in the current block, create a new block and use that.
+        else {
+            // Calculate the size that the next shared block should be.
+            // This is where we implement progressive block growth.  We do the
+            // growth as a factor of the total arena capacity, not just the
+            // current block.
+            let next_shared_size = self.next_shared_block_size();
+
+            // We take the minimum of the over-all arena waste percentage and
+            // the current block's waste percentage because if the current
+            // block is below the threshhold, then we can start a new block
+            // without cumulatively increasing the waste percentage of the
+            // whole arena.
+            let waste_percentage = {
+                let block = blocks.front().unwrap();
+                let w1 = percentage(block.capacity() - block.len(), block.capacity());
+                let w2 = percentage(
+                    self.stat_space_occupied.get() - self.stat_space_allocated.get(),
+                    self.stat_space_occupied.get(),
+                );
+                w1.min(w2)
+            };
+
+            // Are we making a new shared block, or a one-off for this
+            // allocation?
+            let is_shared_block = size.saturating_add(alignment) <= next_shared_size
+                && waste_percentage <= self.max_waste_percentage;
+
+            // Determine the size and alignment of the new block.  One-off
+            // blocks are allocated with the needed alignment directly, so
+            // they don't need any extra room for padding.
+            let (new_block_size, new_block_alignment) = if is_shared_block {
+                (next_shared_size, self.block_alignment)
+            } else if self.guard_source(alignment).is_some() {
+                // Guarded blocks end right where the allocation does, so
+                // they can only be aligned as much as it needs.
+                (size, alignment)
+            } else {
+                (size, alignment.max(self.block_alignment))
+            };
+
+            // Add the new block.
+
+            // Get the new block.
+            let new_block = {
+                if is_shared_block {
+                    // If it's shared, add to the front,
+                    let block =
+                        self.try_new_block(&blocks, new_block_size, new_block_alignment, false)?;
+                    blocks.push_front(block);
+                    blocks.front_mut().unwrap()
+                } else {
+                    // Otherwise add to the the back.
+                    let block =
+                        self.try_new_block(&blocks, new_block_size, new_block_alignment, true)?;
+                    blocks.push_back(block);
+                    #[cfg(feature = "tracing")]
+                    global::unscoped(|| {
+                        tracing::debug!(size, align = alignment, "oversize arena allocation")
+                    });
+                    self.stat_oversize_block_count
+                        .set(self.stat_oversize_block_count.get() + 1);
+                    self.stat_oversize_allocated
+                        .set(self.stat_oversize_allocated.get() + size);
+                    blocks.back_mut().unwrap()
+                }
+            };
+            let new_block_ptr = new_block.as_mut_ptr();
+
+            // Update stats.
+            self.stat_space_allocated
+                .set(self.stat_space_allocated.get() + size);
+
+            // Do the bump allocation.
+            let start_index = alignment_offset(new_block_ptr as usize, alignment);
+            unsafe { new_block.set_len(start_index + size) };
+            self.update_peak_stats(blocks.len());
+
+            // Return the allocation.
+            Ok(unsafe { new_block_ptr.add(start_index) })
+        }
+    }
+
+    /// Same as `alloc_raw()`, but returns the allocation as a
+    /// `NonNull<[u8]>`, following the conventions of the standard library's
+    /// allocator APIs.
+    ///
+    /// The length of the returned slice is the usable size of the
+    /// allocation, which is always exactly `layout.size()`.  The same safety
+    /// caveats as `alloc_raw()` apply.
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub fn alloc_raw_nonnull(&self, layout: Layout) -> NonNull<[u8]> {
+        let memory = self.alloc_raw(layout) as *mut u8;
+        NonNull::slice_from_raw_parts(NonNull::new(memory).unwrap(), layout.size())
+    }
+
+    /// Whether an allocation with `layout` would fit in the current block,
+    /// without needing a new block.
+    ///
+    /// This is cheap, and useful for only using the arena when allocating
+    /// from it is cheap, too.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// # use std::alloc::Layout;
+    /// let arena = Arena::new().with_block_size(64);
+    /// arena.alloc(0u8);
+    /// assert!(arena.will_fit(Layout::new::<[u8; 32]>()));
+    /// assert!(!arena.will_fit(Layout::new::<[u8; 64]>()));
+    /// ```
+    pub fn will_fit(&self, layout: Layout) -> bool {
+        let blocks = self.blocks.borrow();
+        match blocks.front() {
+            Some(block) => {
+                let end_addr = block.as_ptr() as usize + block.len();
+                let padding = alignment_offset(end_addr, layout.align());
+                block.len() + padding + layout.size() <= block.capacity()
+            }
+            None => false,
+        }
+    }
+
+    /// Resizes the allocation at `ptr` from `old_layout` to `new_layout`,
+    /// returning a pointer to the resized allocation.
+    ///
+    /// If `ptr` is the most recent allocation made from the arena and there's
+    /// enough room left in the current block, it's resized in place.
+    /// Shrinking also always happens in place.  Otherwise a new allocation is
+    /// made and the contents are copied over, up to the smaller of the two
+    /// sizes.
+    ///
+    /// Either way, any memory beyond the old size is uninitialized.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be an allocation from this arena, made with `old_layout`
+    /// (or the layout from a previous resize).  After the call, `ptr` must no
+    /// longer be used unless it is equal to the returned pointer.
+    ///
+    /// The other caveats of `alloc_raw()` apply to the returned pointer as
+    /// well.
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub unsafe fn realloc_raw(
+        &self,
+        ptr: *mut MaybeUninit<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> *mut MaybeUninit<u8> {
+        match self.try_realloc_raw(ptr, old_layout, new_layout) {
+            Ok(memory) => memory.as_ptr() as *mut MaybeUninit<u8>,
+            Err(error) => self.alloc_failed(error),
+        }
+    }
+
+    /// Same as `realloc_raw()`, but returns an error if memory can't be
+    /// allocated, rather than aborting.  The original allocation is left
+    /// untouched in that case.
+    ///
+    /// # Safety
+    ///
+    /// Same as `realloc_raw()`.
+    #[cfg_attr(feature = "instrument", track_caller)]
+    pub unsafe fn try_realloc_raw(
+        &self,
+        ptr: *mut MaybeUninit<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, ArenaAllocError> {
+        if (ptr as usize) & (new_layout.align() - 1) == 0 {
+            if self.try_resize_in_place(ptr, old_layout.size(), new_layout.size()) {
+                return Ok(NonNull::new_unchecked(ptr as *mut u8));
+            }
+            if new_layout.size() <= old_layout.size() {
+                return Ok(NonNull::new_unchecked(ptr as *mut u8));
+            }
+        }
+
+        let new_ptr = self.alloc_raw_impl(new_layout)?;
+        std::ptr::copy_nonoverlapping(ptr, new_ptr, old_layout.size().min(new_layout.size()));
+        Ok(NonNull::new_unchecked(new_ptr as *mut u8))
+    }
+
+    /// Gets a new block with at least the given capacity and alignment, and
+    /// counts it towards the occupied space.
+    ///
+    /// Spare blocks kept by `reset()` are reused if possible.  Otherwise the
+    /// block is allocated, from the parent arena if this is a sub-arena.
+    /// `blocks` is the arena's current block list, which is borrowed by the
+    /// caller.  `oversize` is whether it's a one-off block, for the hooks.
+    fn new_block(
+        &self,
+        blocks: &BlockList<A>,
+        capacity: usize,
+        alignment: usize,
+        oversize: bool,
+    ) -> Block<A> {
+        self.try_new_block(blocks, capacity, alignment, oversize)
+            .unwrap_or_else(|error| self.alloc_failed(error))
+    }
+
+    /// Same as `new_block()`, but returns an error if the block can't be
+    /// allocated.
+    fn try_new_block(
+        &self,
+        blocks: &BlockList<A>,
+        capacity: usize,
+        alignment: usize,
+        oversize: bool,
+    ) -> Result<Block<A>, ArenaAllocError> {
+        // Oversized allocations always get a new block with guard pages, if
+        // enabled.
+        let guard_source = self.guard_source(alignment).filter(|_| oversize);
+        let spare_block = match guard_source {
+            Some(_) => None,
+            None => self.take_spare_block(capacity, alignment),
+        };
+
+        let block = match spare_block {
+            Some(block) => block,
+            None => {
+                self.check_memory_limit(blocks, capacity)?;
+                let block = match &self.parent {
+                    Some(parent) => parent.try_alloc_block(capacity, alignment)?,
+                    None => match guard_source.or(self.block_source.as_ref()) {
+                        Some(source) => Block::try_new_in(capacity, alignment, source)?,
+                        None => Block::try_new(capacity, alignment, &self.allocator)?,
+                    },
+                };
+                // Block sources can hand out more memory than was asked for.
+                if block.alloc_size() > capacity {
+                    self.check_memory_limit(blocks, block.alloc_size())?;
+                }
+                self.call_hooks(|hooks| hooks.on_new_block(capacity, oversize));
+                #[cfg(feature = "tracing")]
+                global::unscoped(|| tracing::debug!(size = capacity, oversize, "new arena block"));
+                block
+            }
+        };
+
+        self.stat_space_occupied
+            .set(self.stat_space_occupied.get() + block.capacity());
+
+        Ok(block)
+    }
+
+    /// The source for guarded oversized blocks with the given alignment, if
+    /// guard pages are enabled and can be used for them.
+    #[cfg(all(unix, feature = "guard_pages"))]
+    #[inline(always)]
+    fn guard_source(&self, alignment: usize) -> Option<&Arc<dyn BlockSource>> {
+        self.guard_source
+            .as_ref()
+            .filter(|_| self.parent.is_none() && alignment <= mmap::page_size())
+    }
+
+    #[cfg(not(all(unix, feature = "guard_pages")))]
+    #[inline(always)]
+    fn guard_source(&self, _alignment: usize) -> Option<&Arc<dyn BlockSource>> {
+        None
+    }
+
+    /// Returns an error if allocating a new block of `size` bytes would take
+    /// the arena over its memory limit.
+    ///
+    /// All of the memory the arena's blocks take up counts, including spare
+    /// blocks and capacity hidden by `shrink_to_fit()`.  If freeing the spare
+    /// blocks makes enough room, they're freed.
+    fn check_memory_limit(
+        &self,
+        blocks: &BlockList<A>,
+        size: usize,
+    ) -> Result<(), ArenaAllocError> {
+        let limit = match self.memory_limit {
+            Some(limit) => limit,
+            None => return Ok(()),
+        };
+
+        let mut spare_blocks = self.spare_blocks.borrow_mut();
+        let used: usize = blocks.iter().map(|b| b.alloc_size()).sum();
+        let spare: usize = spare_blocks.iter().map(|b| b.alloc_size()).sum();
+        if size > limit.saturating_sub(used.saturating_add(spare)) {
+            if size > limit.saturating_sub(used) {
+                return Err(ArenaAllocError::MemoryLimitExceeded);
+            }
+            spare_blocks.clear();
+        }
+        Ok(())
+    }
+
+    /// Calls `f` with the hooks, if there are any.
+    ///
+    /// Panics if it's called from within a hook, since that means a hook is
+    /// using the arena.
+    #[inline(always)]
+    fn call_hooks<F: FnOnce(&dyn ArenaHooks)>(&self, f: F) {
+        struct Guard<'a>(&'a Cell<bool>);
+        impl Drop for Guard<'_> {
+            fn drop(&mut self) {
+                self.0.set(false);
+            }
+        }
+
+        if let Some(hooks) = &self.hooks {
+            assert!(
+                !self.in_hooks.get(),
+                "Arena hooks must not use the arena they're registered with"
+            );
+            self.in_hooks.set(true);
+            let _guard = Guard(&self.in_hooks);
+            global::unscoped(|| f(&**hooks));
+        }
+    }
+
+    /// Reports an allocation failure from one of the infallible methods,
+    /// according to the OOM policy.
+    #[cold]
+    fn alloc_failed(&self, error: ArenaAllocError) -> ! {
+        match error {
+            ArenaAllocError::LayoutOverflow => panic!("Invalid block layout"),
+            ArenaAllocError::MemoryLimitExceeded => panic!("Arena memory limit exceeded"),
+            ArenaAllocError::OutOfMemory(layout) => match self.oom_policy {
+                OomPolicy::Abort => std::alloc::handle_alloc_error(layout),
+                OomPolicy::Panic => panic!(
+                    "Arena failed to allocate a block of {} bytes",
+                    layout.size()
+                ),
+                OomPolicy::Handler(handler) => handler(layout),
+            },
+        }
+    }
+
+    /// Updates the high-water marks with the current allocated space and
+    /// block count.
+    #[inline(always)]
+    fn update_peak_stats(&self, block_count: usize) {
+        self.stat_peak_allocated.set(
+            self.stat_peak_allocated
+                .get()
+                .max(self.stat_space_allocated.get()),
+        );
+        self.stat_peak_block_count
+            .set(self.stat_peak_block_count.get().max(block_count));
+    }
+
+    /// Removes and returns the smallest spare block that satisfies the given
+    /// capacity and alignment, if any.
+    fn take_spare_block(&self, capacity: usize, alignment: usize) -> Option<Block<A>> {
+        let mut spare_blocks = self.spare_blocks.borrow_mut();
+        let index = spare_blocks
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| {
+                b.capacity() >= capacity && (b.as_ptr() as usize & (alignment - 1)) == 0
+            })
+            .min_by_key(|(_, b)| b.capacity())
+            .map(|(i, _)| i)?;
+        Some(spare_blocks.swap_remove(index))
+    }
+
+    /// The size that the next shared block should be, according to the
+    /// growth strategy.
+    fn next_shared_block_size(&self) -> usize {
+        match self.growth_strategy {
+            GrowthStrategy::Constant => self.min_block_size,
+            GrowthStrategy::Percentage(perc) => {
+                // Capped so that the block layout stays valid even for
+                // percentages over 100.
+                let a = (self.stat_space_occupied.get() / 100)
+                    .saturating_mul(perc as usize)
+                    .min(isize::MAX as usize / 2);
+                let b = a % self.min_block_size;
+                self.min_block_size.max(a - b)
+            }
+        }
+    }
+
+    /// The number of `T`s that can be allocated as an array without starting
+    /// a new block, or, if that's zero, the number that would fit in a new
+    /// shared block.  Always at least one.
+    fn chunk_capacity<T>(&self) -> usize {
+        let blocks = self.blocks.borrow();
+        let remaining = match blocks.front() {
+            Some(block) => {
+                let end_addr = block.as_ptr() as usize + block.len();
+                let padding = end_addr.wrapping_neg() & (std::mem::align_of::<T>() - 1);
+                block.capacity().saturating_sub(block.len() + padding)
+            }
+            None => 0,
+        };
+
+        if remaining >= size_of::<T>() {
+            remaining / size_of::<T>()
+        } else {
+            let block_size = self.next_shared_block_size();
+            (block_size.saturating_sub(std::mem::align_of::<T>()) / size_of::<T>()).max(1)
         }
+    }
 
-        let alignment = layout.align();
-        let size = layout.size();
+    /// Whether `ptr` points into memory that's been allocated from the arena.
+    ///
+    /// Memory that's been freed (e.g. by clearing the arena) or allocated
+    /// from one of its regions doesn't count.  Useful for debugging, and for
+    /// asserting that inputs were allocated from a particular arena.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let arena = Arena::new();
+    /// let a = arena.alloc(1u32);
+    /// let b = Box::new(2u32);
+    /// assert!(arena.owns(a));
+    /// assert!(!arena.owns(&*b));
+    /// ```
+    ///
+    /// Always false while the arena is busy managing its blocks.
+    pub fn owns<T: ?Sized>(&self, ptr: *const T) -> bool {
+        let addr = ptr as *const u8 as usize;
+        let blocks = match self.blocks.try_borrow() {
+            Ok(blocks) => blocks,
+            Err(_) => return false,
+        };
+        blocks.iter().any(|block| {
+            let start = block.as_ptr() as usize;
+            addr >= start && addr < start + block.len()
+        })
+    }
 
-        let mut blocks = self.blocks.borrow_mut();
+    /// Whether the arena is in the middle of managing its blocks, handles, or
+    /// regions, and thus can't be allocated from right now.
+    pub(crate) fn is_busy(&self) -> bool {
+        self.blocks.try_borrow_mut().is_err()
+            || self.handles.is_busy()
+            || self.regions.try_borrow_mut().is_err()
+    }
 
-        // Add the first block if we're empty.
-        if blocks.is_empty() {
-            blocks.push_front(Vec::with_capacity(self.min_block_size));
+    /// Attempts to resize the allocation at `ptr` in place, without moving
+    /// it.
+    ///
+    /// This only succeeds if the allocation is the most recent one in the
+    /// current block and, when growing, there is enough room left in the
+    /// block.  Returns whether the resize succeeded.
+    pub(crate) fn try_resize_in_place(
+        &self,
+        ptr: *const MaybeUninit<u8>,
+        old_size: usize,
+        new_size: usize,
+    ) -> bool {
+        let mut blocks = self.blocks.borrow_mut();
+        let cur_block = match blocks.front_mut() {
+            Some(block) => block,
+            None => return false,
+        };
 
-            // Update stats
-            self.stat_space_occupied
-                .set(self.stat_space_occupied.get() + self.min_block_size);
+        // Make sure it's the most recent allocation in the current block.
+        let block_addr = cur_block.as_ptr() as usize;
+        let addr = ptr as usize;
+        if old_size == 0 || addr < block_addr || (addr + old_size) != (block_addr + cur_block.len())
+        {
+            return false;
         }
 
-        // If we're zero-sized, just put us at the start of the current block.
-        if size == 0 {
-            return blocks.front_mut().unwrap().as_mut_ptr();
+        // Make sure the new size fits.
+        let start_index = addr - block_addr;
+        if (start_index + new_size) > cur_block.capacity() {
+            return false;
         }
 
-        // Find our starting index for if we're allocating in the current block.
-        let start_index_proposal = {
-            let cur_block = blocks.front().unwrap();
-            let block_addr = cur_block.as_ptr() as usize;
-            let block_filled = cur_block.len();
-            block_filled + alignment_offset(block_addr + block_filled, alignment)
-        };
+        // Do the resize.
+        unsafe { cur_block.set_len(start_index + new_size) };
 
-        // If it will fit in the current block, use the current block.
-        if (start_index_proposal + size) <= blocks.front().unwrap().capacity() {
-            let cur_block = blocks.front_mut().unwrap();
+        // Update stats.
+        self.stat_space_allocated
+            .set(self.stat_space_allocated.get() - old_size + new_size);
+        self.update_peak_stats(blocks.len());
 
-            // Do the bump allocation.
-            let new_len = (start_index_proposal + size).max(cur_block.len());
-            unsafe { cur_block.set_len(new_len) };
+        true
+    }
 
-            // Update stats.
-            self.stat_space_allocated
-                .set(self.stat_space_allocated.get() + size);
+    //------------------------------------------------------------------------
+    // Resizing methods.
 
-            // Return the allocation.
-            unsafe { cur_block.as_mut_ptr().add(start_index_proposal) }
+    /// Extends `slice` in place with the contents of `additional`, if
+    /// possible.
+    ///
+    /// This only works if `slice` is the most recent allocation made from
+    /// the arena, and there's enough room left in the current block.  Returns
+    /// whether the extension succeeded.  On failure, `slice` is left
+    /// unchanged.
+    ///
+    /// Empty slices can never be extended, since their position in the arena
+    /// is ambiguous.
+    #[inline]
+    pub fn extend_last_slice<'a, T: Copy>(
+        &'a self,
+        slice: &mut &'a mut [T],
+        additional: &[T],
+    ) -> bool {
+        let len = slice.len();
+        let old_size = size_of_val(*slice);
+        let ptr = slice.as_mut_ptr();
+
+        if !self.try_resize_in_place(
+            ptr as *const MaybeUninit<u8>,
+            old_size,
+            old_size + size_of_val(additional),
+        ) {
+            return false;
         }
-        // If it won't fit in the current block, create a new block and use that.
-        else {
-            // Calculate the size that the next shared block should be.
-            // This is where we implement progressive block growth.  We do the
-            // growth as a factor of the total arena capacity, not just the
-            // current block.
-            let next_shared_size = match self.growth_strategy {
-                GrowthStrategy::Constant => self.min_block_size,
-                GrowthStrategy::Percentage(perc) => {
-                    let a = self.stat_space_occupied.get() / 100 * perc as usize;
-                    let b = a % self.min_block_size;
-                    self.min_block_size.max(a - b)
-                }
-            };
-
-            // We take the minimum of the over-all arena waste percentage and
-            // the current block's waste percentage because if the current
-            // block is below the threshhold, then we can start a new block
-            // without cumulatively increasing the waste percentage of the
-            // whole arena.
-            let waste_percentage = {
-                let block = blocks.front().unwrap();
-                let w1 = ((block.capacity() - block.len()) * 100) / block.capacity();
-                let w2 = ((self.stat_space_occupied.get() - self.stat_space_allocated.get()) * 100)
-                    / self.stat_space_occupied.get();
-                w1.min(w2)
-            };
-
-            // Are we making a new shared block, or a one-off for this
-            // allocation?
-            let is_shared_block = (size + alignment) <= next_shared_size
-                && waste_percentage <= self.max_waste_percentage;
 
-            // Determine the size of the new block.
-            let new_block_size = if is_shared_block {
-                next_shared_size
-            } else {
-                size + alignment - 1
-            };
+        unsafe {
+            std::ptr::copy_nonoverlapping(additional.as_ptr(), ptr.add(len), additional.len());
+            *slice = slice::from_raw_parts_mut(ptr, len + additional.len());
+        }
 
-            // Update stats.
-            self.stat_space_occupied
-                .set(self.stat_space_occupied.get() + new_block_size);
-            self.stat_space_allocated
-                .set(self.stat_space_allocated.get() + size);
+        true
+    }
 
-            // Add the new block.
+    /// Shortens `slice` to `new_len` elements, handing the memory of the
+    /// removed tail back to the arena if possible.
+    ///
+    /// The memory can only be handed back if `slice` is the most recent
+    /// allocation made from the arena, in which case subsequent allocations
+    /// will reuse it.  Returns whether that happened.  Either way, `slice` is
+    /// shortened.
+    ///
+    /// Panics if `new_len` is greater than the length of `slice`.
+    #[inline]
+    pub fn shrink_last_slice<'a, T: Copy>(
+        &'a self,
+        slice: &mut &'a mut [T],
+        new_len: usize,
+    ) -> bool {
+        assert!(
+            new_len <= slice.len(),
+            "Cannot shrink a slice to a greater length"
+        );
 
-            // Get the new block.
-            let new_block = {
-                if is_shared_block {
-                    // If it's shared, add to the front,
-                    blocks.push_front(Vec::with_capacity(new_block_size));
-                    blocks.front_mut().unwrap()
-                } else {
-                    // Otherwise add to the the back.
-                    blocks.push_back(Vec::with_capacity(new_block_size));
-                    blocks.back_mut().unwrap()
-                }
-            };
+        let old_size = size_of_val(*slice);
+        let reclaimed = self.try_resize_in_place(
+            slice.as_ptr() as *const MaybeUninit<u8>,
+            old_size,
+            size_of::<T>() * new_len,
+        );
 
-            // Do the bump allocation.
-            let start_index = alignment_offset(new_block.as_ptr() as usize, alignment);
-            unsafe { new_block.set_len(start_index + size) };
+        let tmp = std::mem::take(slice);
+        *slice = &mut tmp[..new_len];
 
-            // Return the allocation.
-            unsafe { new_block.as_mut_ptr().add(start_index) }
-        }
+        reclaimed
     }
 
     //------------------------------------------------------------------------
     // Misc methods.
 
     /// Frees all memory currently allocated by the arena.
+    ///
+    /// Blocks are kept for reuse according to the arena's retention policy,
+    /// which by default keeps nothing.
     pub fn clear(&mut self) {
         unsafe { self.clear_unchecked() }
     }
@@ -494,50 +2674,734 @@ impl Arena {
     /// quite happily free all of its memory even with hundreds or thousands
     /// of outstanding references pointing to it.
     pub unsafe fn clear_unchecked(&self) {
+        self.clear_unchecked_with(self.retention_policy);
+    }
+
+    /// Same as `clear()`, but with the given retention policy instead of the
+    /// arena's own.
+    ///
+    /// ```rust
+    /// # use kioku::{Arena, RetentionPolicy};
+    /// let mut arena = Arena::new();
+    /// arena.alloc_array(0u8, 100);
+    /// arena.clear_with(RetentionPolicy::UpToBytes(1 << 20));
+    /// ```
+    pub fn clear_with(&mut self, retention_policy: RetentionPolicy) {
+        unsafe { self.clear_unchecked_with(retention_policy) }
+    }
+
+    /// Frees everything allocated in the arena like `clear()`, but keeps all
+    /// of its blocks for reuse.
+    ///
+    /// This avoids repeatedly allocating and freeing the same blocks when the
+    /// arena is used for a similar workload over and over, such as once per
+    /// frame in a game.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let mut arena = Arena::new();
+    /// let a = arena.alloc_array(0u8, 100).as_ptr();
+    /// arena.reset();
+    /// let b = arena.alloc_array(0u8, 100).as_ptr();
+    /// assert_eq!(a, b);
+    /// ```
+    pub fn reset(&mut self) {
+        self.clear_with(RetentionPolicy::Everything);
+    }
+
+    /// Ensures that at least `additional` contiguous bytes can be allocated
+    /// without the arena needing a new block.
+    ///
+    /// If the current block doesn't have enough room left, a new block is
+    /// started right away.  This lets you take the cost of allocating blocks
+    /// up front, before a performance-sensitive section of code.  Note that
+    /// alignment padding is taken from the same space.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let arena = Arena::new().with_block_size(64);
+    /// arena.reserve(1000);
+    /// let a = arena.alloc_array(0u8, 500).as_ptr();
+    /// let b = arena.alloc_array(0u8, 500).as_ptr();
+    /// assert_eq!(unsafe { a.add(500) }, b);
+    /// ```
+    pub fn reserve(&self, additional: usize) {
+        let mut blocks = self.blocks.borrow_mut();
+        if let Some(block) = blocks.front() {
+            if block.capacity() - block.len() >= additional {
+                return;
+            }
+        }
+
+        let size = additional.max(self.next_shared_block_size());
+        let block = self.new_block(&blocks, size, self.block_alignment, false);
+        blocks.push_front(block);
+        self.update_peak_stats(blocks.len());
+    }
+
+    /// Takes ownership of `buffer` and makes it the arena's current block,
+    /// so that the following allocations are made from it.
+    ///
+    /// This lets large buffers you already have be recycled as arena memory,
+    /// instead of the arena allocating new blocks.  The buffer's whole
+    /// capacity is used, and its contents are discarded.  Afterwards it's
+    /// treated like any other block, so it's freed or kept for reuse when
+    /// the arena is cleared.  Any room left in the previous current block
+    /// goes unused.
+    ///
+    /// The start of the buffer is skipped as needed to align the block to
+    /// the arena's block alignment.  Buffers too small for that are freed.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let arena = Arena::new();
+    /// let buffer: Vec<u8> = Vec::with_capacity(4096);
+    /// let addr = buffer.as_ptr();
+    ///
+    /// arena.adopt_block(buffer);
+    /// assert_eq!(addr, arena.alloc_array(0u8, 4000).as_ptr());
+    /// ```
+    pub fn adopt_block<B: Into<Vec<u8>>>(&self, buffer: B) {
+        let buffer = buffer.into();
+        if buffer.capacity() == 0 {
+            return;
+        }
+
+        let block = Block::from_vec(buffer, self.block_alignment);
+        if block.capacity() == 0 {
+            return;
+        }
+        self.stat_space_occupied
+            .set(self.stat_space_occupied.get() + block.capacity());
+        let mut blocks = self.blocks.borrow_mut();
+        blocks.push_front(block);
+        self.update_peak_stats(blocks.len());
+    }
+
+    /// Stops using the unused end of the current block, after a phase of
+    /// building data that won't be added to.
+    ///
+    /// For a sub-arena, the unused memory is given back to the parent arena
+    /// if possible.  Otherwise it stays allocated until the block is freed,
+    /// since the global allocator can't shrink allocations in place, but it's
+    /// no longer counted as occupied space.  That keeps it from counting as
+    /// waste when the arena decides how to allocate new blocks.  Either way,
+    /// later allocations go in a new block.
+    ///
+    /// Blocks kept for reuse after a `reset()` get their full capacity back.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let parent = Arena::new().with_block_size(1024);
+    /// let before = parent.alloc(1u128) as *mut u128;
+    /// let child = parent.sub_arena();
+    /// child.alloc_array(0u128, 4);
+    /// child.shrink_to_fit();
+    ///
+    /// // The rest of the child's block went back to the parent.
+    /// let after = parent.alloc(2u128) as *mut u128;
+    /// assert_eq!(unsafe { before.add(5) }, after);
+    /// ```
+    pub fn shrink_to_fit(&self) {
         let mut blocks = self.blocks.borrow_mut();
+        let block = match blocks.front_mut() {
+            // An empty block would have no capacity left at all.
+            Some(block) if block.len() > 0 => block,
+            _ => return,
+        };
+
+        self.stat_space_occupied
+            .set(self.stat_space_occupied.get() - (block.capacity() - block.len()));
+        match &self.parent {
+            Some(parent) if parent.give_back_unused(block) => block.give_up_unused(),
+            _ => block.shrink_to_len(),
+        }
+    }
 
-        blocks.clear();
+    /// Frees blocks that the arena is holding on to without using, and
+    /// returns the number of bytes freed.
+    ///
+    /// This includes blocks kept for reuse by `reset()` or the retention
+    /// policy, as well as any blocks other than the current one that have
+    /// nothing allocated in them.  Allocated memory is left untouched.
+    /// Useful for long-running programs to give memory back after a spike
+    /// in usage.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let mut arena = Arena::new().with_block_size(1024);
+    /// arena.alloc_array(0u8, 100);
+    /// arena.reset();
+    /// assert_eq!(1024, arena.trim());
+    /// ```
+    pub fn trim(&mut self) -> usize {
+        let mut freed = 0;
+        for (_, region) in self.regions.get_mut().iter_mut() {
+            freed += region.trim();
+        }
+
+        for block in self.spare_blocks.get_mut().drain(..) {
+            freed += block.capacity();
+        }
+
+        // The front block is kept even if it's empty, since it's the one
+        // that new allocations go into.  Otherwise a one-off block could take
+        // its place.
+        let blocks = self.blocks.get_mut();
+        let mut old_blocks = std::mem::take(blocks);
+        if let Some(block) = old_blocks.pop_front() {
+            blocks.push_back(block);
+        }
+        for block in old_blocks {
+            if block.len() == 0 {
+                freed += block.capacity();
+                self.stat_space_occupied
+                    .set(self.stat_space_occupied.get() - block.capacity());
+            } else {
+                blocks.push_back(block);
+            }
+        }
+
+        freed
+    }
+
+    unsafe fn clear_unchecked_with(&self, retention_policy: RetentionPolicy) {
+        self.drop_list.run();
+        self.handles.truncate(0);
+        if retention_policy == RetentionPolicy::Nothing {
+            self.regions.borrow_mut().clear();
+        } else {
+            for (_, region) in self.regions.borrow().iter() {
+                region.clear_unchecked_with(retention_policy);
+            }
+        }
+
+        let mut blocks = self.blocks.borrow_mut();
+        let mut spare_blocks = self.spare_blocks.borrow_mut();
+        if retention_policy == RetentionPolicy::Nothing {
+            blocks.clear();
+            spare_blocks.clear();
+        } else {
+            spare_blocks.extend(std::mem::take(&mut *blocks));
+        }
+        for block in spare_blocks.iter_mut() {
+            block.reset();
+        }
 
+        match retention_policy {
+            RetentionPolicy::Nothing => {}
+            RetentionPolicy::LargestBlock => {
+                spare_blocks.sort_unstable_by_key(|b| std::cmp::Reverse(b.capacity()));
+                spare_blocks.truncate(1);
+            }
+            RetentionPolicy::UpToBytes(max_bytes) => {
+                // Keep the largest blocks that fit.
+                spare_blocks.sort_unstable_by_key(|b| std::cmp::Reverse(b.capacity()));
+                let mut total = 0;
+                spare_blocks.retain(|b| {
+                    if total + b.capacity() <= max_bytes {
+                        total += b.capacity();
+                        true
+                    } else {
+                        false
+                    }
+                });
+            }
+            RetentionPolicy::Everything => {}
+        }
         self.stat_space_occupied.set(0);
         self.stat_space_allocated.set(0);
+        self.stat_oversize_block_count.set(0);
+        self.stat_oversize_allocated.set(0);
+        self.stat_alloc_count.set(0);
+    }
+
+    /// Consumes the arena without freeing its memory, returning a `'static`
+    /// reference to it.
+    ///
+    /// Anything allocated through the returned reference lives for the rest
+    /// of the program, which is handy for long-lived data (such as interned
+    /// strings) built at startup.  Since references borrow the arena they
+    /// came from, this has to be called before making those allocations.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let arena: &'static Arena = Arena::new().leak();
+    /// let name: &'static str = arena.copy_str("kioku");
+    /// assert_eq!("kioku", name);
+    /// ```
+    ///
+    /// Values allocated with `alloc_droppable()` are never dropped.
+    pub fn leak(self) -> &'static Arena<A>
+    where
+        A: 'static,
+    {
+        Box::leak(Box::new(self))
+    }
+
+    /// Runs `f` with a temporary region of the arena, which is rolled back
+    /// when `f` returns.
+    ///
+    /// Everything allocated in `f` is freed afterwards (and anything
+    /// allocated with `alloc_droppable()` is dropped), while memory allocated
+    /// before the call is left alone.  The lifetimes ensure that nothing
+    /// allocated in `f` can escape it.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let mut arena = Arena::new();
+    /// let sum: u32 = arena.scope(|arena| arena.alloc_array(2, 100).iter().sum());
+    /// assert_eq!(200, sum);
+    ///
+    /// // The memory is reused by later allocations.
+    /// let a = arena.scope(|arena| arena.alloc(1u32) as *mut u32);
+    /// let b = arena.scope(|arena| arena.alloc(2u32) as *mut u32);
+    /// assert_eq!(a, b);
+    /// ```
+    ///
+    /// Since this takes `&mut self`, any references into the arena from
+    /// before the call must be dropped first.
+    pub fn scope<R, F: FnOnce(&Arena<A>) -> R>(&mut self, f: F) -> R {
+        // The rewind happens even if `f` panics.
+        f(&self.temp())
+    }
+
+    /// Starts a temporary region of the arena, which is rolled back when the
+    /// returned guard is dropped.
+    ///
+    /// This is the same as `scope()`, but with a guard object rather than a
+    /// closure.  See `TempScope` for details.
+    pub fn temp(&mut self) -> TempScope<'_, A> {
+        TempScope::new(self)
+    }
+
+    /// Creates a sub-arena, whose memory is allocated from this arena and
+    /// given back when the sub-arena is dropped.
+    ///
+    /// See `SubArena` for details.
+    pub fn sub_arena(&self) -> SubArena<'_, A> {
+        SubArena::new(self)
+    }
+
+    /// The named region `name` of the arena, which is created on first use.
+    ///
+    /// Regions are separate arenas with their own blocks, which can be
+    /// cleared independently with `clear_region()`.  This lets a single
+    /// `Arena` be passed around for data with different lifetimes, such as
+    /// per-frame and persistent data in a game.  They have the same settings
+    /// as this arena, and are cleared along with it by `clear()`.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let mut arena = Arena::new();
+    /// let level = arena.alloc_handle(1u32);
+    /// for frame in 0..3u32 {
+    ///     let scratch = arena.region("frame").alloc_array(frame, 100);
+    ///     assert_eq!(frame * 100, scratch.iter().sum::<u32>());
+    ///     arena.clear_region("frame");
+    /// }
+    /// assert_eq!(Some(&1), arena.get(level));
+    /// ```
+    pub fn region(&self, name: &str) -> &Arena<A> {
+        let mut regions = self.regions.borrow_mut();
+        let region: *const Arena<A> = match regions.iter().find(|(n, _)| n == name) {
+            Some((_, region)) => region,
+            None => {
+                regions.push_back((name.into(), self.new_with_same_settings()));
+                &regions.back().unwrap().1
+            }
+        };
+
+        // Safe because linked list nodes never move, and regions are only
+        // removed by methods that take `&mut self`.
+        unsafe { &*region }
+    }
+
+    /// Mutable access to the named region `name`, if it exists.
+    pub fn region_mut(&mut self, name: &str) -> Option<&mut Arena<A>> {
+        self.regions
+            .get_mut()
+            .iter_mut()
+            .find(|(n, _)| n == name)
+            .map(|(_, region)| region)
+    }
+
+    /// Frees all memory currently allocated in the named region `name`.
+    ///
+    /// Does nothing if the region doesn't exist.
+    pub fn clear_region(&mut self, name: &str) {
+        if let Some(region) = self.region_mut(name) {
+            region.clear();
+        }
+    }
+
+    /// A new empty arena with the same settings as this one.
+    pub(crate) fn new_with_same_settings(&self) -> Arena<A> {
+        self.new_with_same_settings_in(self.allocator.clone())
+    }
+
+    /// Same as `new_with_same_settings()`, but with a different allocator.
+    fn new_with_same_settings_in<B: Allocator + Clone>(&self, allocator: B) -> Arena<B> {
+        let arena = Arena::new_in(allocator)
+            .with_block_size(self.min_block_size)
+            .with_block_alignment(self.block_alignment)
+            .with_max_waste_percentage(self.max_waste_percentage)
+            .with_growth_strategy(self.growth_strategy)
+            .with_retention_policy(self.retention_policy)
+            .with_oom_policy(self.oom_policy);
+        Arena {
+            memory_limit: self.memory_limit,
+            block_source: self.block_source.clone(),
+            #[cfg(all(unix, feature = "guard_pages"))]
+            guard_source: self.guard_source.clone(),
+            ..arena
+        }
+    }
+
+    /// Records the current allocation state of the arena, for `rewind()`.
+    pub(crate) fn checkpoint(&self) -> Checkpoint {
+        let blocks = self.blocks.borrow();
+        Checkpoint {
+            front: blocks.front().map(|b| (b.as_ptr(), b.len())),
+            back: blocks.back().map(|b| b.as_ptr()),
+            drop_mark: self.drop_list.mark(),
+            handle_count: self.handles.len(),
+            stat_space_occupied: self.stat_space_occupied.get(),
+            stat_space_allocated: self.stat_space_allocated.get(),
+            stat_oversize_block_count: self.stat_oversize_block_count.get(),
+            stat_oversize_allocated: self.stat_oversize_allocated.get(),
+        }
+    }
+
+    /// Frees everything allocated since `checkpoint` was taken, dropping
+    /// any droppable values among it.
+    ///
+    /// # Safety
+    ///
+    /// `checkpoint` must come from this arena, and not have been rewound past
+    /// since.  None of the memory allocated since it was taken may be in use
+    /// anymore.
+    pub(crate) unsafe fn rewind(&self, checkpoint: &Checkpoint) {
+        self.drop_list.run_to(checkpoint.drop_mark);
+        self.handles.truncate(checkpoint.handle_count);
+
+        let mut blocks = self.blocks.borrow_mut();
+        match checkpoint.front {
+            None => blocks.clear(),
+            Some((front, len)) => {
+                // New shared blocks are added to the front and one-off blocks
+                // to the back, so the original blocks are all in between.
+                while blocks.front().unwrap().as_ptr() != front {
+                    blocks.pop_front();
+                }
+                while Some(blocks.back().unwrap().as_ptr()) != checkpoint.back {
+                    blocks.pop_back();
+                }
+                blocks.front_mut().unwrap().set_len(len);
+            }
+        }
+
+        self.stat_space_occupied.set(checkpoint.stat_space_occupied);
+        self.stat_space_allocated
+            .set(checkpoint.stat_space_allocated);
+        self.stat_oversize_block_count
+            .set(checkpoint.stat_oversize_block_count);
+        self.stat_oversize_allocated
+            .set(checkpoint.stat_oversize_allocated);
+    }
+
+    /// Iterates over the used part of each of the arena's blocks.
+    ///
+    /// This is everything allocated from the arena, including any padding
+    /// between allocations, which is why the bytes may be uninitialized.
+    /// The blocks are in no particular order, and regions aren't included.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let mut arena = Arena::new();
+    /// arena.copy_str("Hello");
+    /// arena.copy_str(" world!");
+    ///
+    /// let chunks: Vec<_> = arena.allocated_chunks().collect();
+    /// assert_eq!(1, chunks.len());
+    /// assert_eq!(12, chunks[0].len());
+    /// ```
+    pub fn allocated_chunks(&mut self) -> impl Iterator<Item = &[MaybeUninit<u8>]> {
+        self.blocks.get_mut().iter().map(|block| block.as_slice())
+    }
+
+    /// Returns statistics about the arena's current memory usage.
+    ///
+    /// Only the arena's own blocks and allocations are counted, not those of
+    /// its regions.  The peak statistics and `total_alloc_count` are kept
+    /// when the arena is cleared, for sizing the arena's settings from a
+    /// representative run.  Allocation counts include allocations freed by a
+    /// `TempScope` or `StackBox`, since they were still made.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let arena = Arena::new().with_block_size(1024);
+    /// arena.alloc_array(0u8, 100);
+    /// arena.alloc_array(0u8, 2000);
+    ///
+    /// let stats = arena.stats();
+    /// assert_eq!(2100, stats.allocated);
+    /// assert_eq!(2, stats.block_count);
+    /// assert_eq!(1, stats.oversize_block_count);
+    /// assert_eq!(2000, stats.oversize_allocated);
+    /// assert_eq!(2, stats.alloc_count);
+    /// ```
+    pub fn stats(&self) -> ArenaStats {
+        ArenaStats {
+            occupied: self.stat_space_occupied.get(),
+            allocated: self.stat_space_allocated.get(),
+            block_count: self.blocks.borrow().len(),
+            oversize_block_count: self.stat_oversize_block_count.get(),
+            oversize_allocated: self.stat_oversize_allocated.get(),
+            alloc_count: self.stat_alloc_count.get(),
+            total_alloc_count: self.stat_total_alloc_count.get(),
+            peak_allocated: self.stat_peak_allocated.get(),
+            peak_block_count: self.stat_peak_block_count.get(),
+        }
+    }
+
+    /// Resets the arena's allocation counts and peak statistics, for
+    /// measuring them over a single phase of work, such as a frame.
+    ///
+    /// The allocation counts are zeroed, and the peaks are set to the
+    /// current values.  The statistics that describe the arena's current
+    /// memory, such as `occupied` and `allocated`, are unaffected, as is the
+    /// memory itself.  With the `instrument` feature, the histogram and call
+    /// sites are cleared as well.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let arena = Arena::new();
+    /// arena.alloc_array(0u8, 100);
+    /// arena.reset_stats();
+    /// arena.alloc_array(0u8, 10);
+    ///
+    /// let stats = arena.stats();
+    /// assert_eq!(1, stats.alloc_count);
+    /// assert_eq!(1, stats.total_alloc_count);
+    /// assert_eq!(110, stats.allocated);
+    /// ```
+    pub fn reset_stats(&self) {
+        self.stat_alloc_count.set(0);
+        self.stat_total_alloc_count.set(0);
+        self.stat_peak_allocated
+            .set(self.stat_space_allocated.get());
+        self.stat_peak_block_count.set(self.blocks.borrow().len());
+        #[cfg(feature = "instrument")]
+        {
+            *self.instrumentation.borrow_mut() = instrument::Instrumentation::new();
+        }
+    }
+
+    /// Returns a histogram of the sizes and alignments of the allocations
+    /// made from the arena.
+    ///
+    /// Like the peak statistics, this covers everything since the arena was
+    /// created or `reset_stats()` was last called, and isn't reset when the
+    /// arena is cleared.  Only available with the `instrument` feature.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let arena = Arena::new();
+    /// arena.alloc(1u32);
+    /// arena.alloc(2u32);
+    /// arena.alloc_array(0u64, 3);
+    ///
+    /// let histogram = arena.alloc_histogram();
+    /// assert_eq!(vec![(4, 2), (32, 1)], histogram.sizes().collect::<Vec<_>>());
+    /// assert_eq!(vec![(4, 2), (8, 1)], histogram.alignments().collect::<Vec<_>>());
+    /// ```
+    #[cfg(feature = "instrument")]
+    pub fn alloc_histogram(&self) -> AllocHistogram {
+        self.instrumentation.borrow().histogram
+    }
+
+    /// Returns how much was allocated from each place in the code that
+    /// allocated from the arena, largest first.
+    ///
+    /// Like the peak statistics, this covers everything since the arena was
+    /// created or `reset_stats()` was last called, and isn't reset when the
+    /// arena is cleared.  Only available with the `instrument` feature.
+    ///
+    /// Allocations are attributed to the code that called the arena's
+    /// allocation method.  Allocations made indirectly, e.g. through a
+    /// `SyncArena` or a collection, are attributed to code within this
+    /// crate instead.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let arena = Arena::new();
+    /// for _ in 0..10 {
+    ///     arena.alloc_array(0u8, 100);
+    /// }
+    /// arena.alloc(0u32);
+    ///
+    /// let call_sites = arena.call_sites();
+    /// assert_eq!(1000, call_sites[0].allocated);
+    /// assert_eq!(10, call_sites[0].alloc_count);
+    /// println!("Biggest allocator: {}", call_sites[0].location);
+    /// ```
+    #[cfg(feature = "instrument")]
+    pub fn call_sites(&self) -> Vec<CallSite> {
+        self.instrumentation.borrow().call_sites()
+    }
+
+    /// Returns a human-readable summary of the arena's memory usage, for
+    /// logging and tuning the arena's settings.
+    ///
+    /// The exact format may change, so it shouldn't be parsed.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let arena = Arena::new().with_block_size(1024);
+    /// arena.alloc_array(0u8, 100);
+    /// arena.alloc_array(0u8, 2000);
+    /// println!("{}", arena.report());
+    /// ```
+    ///
+    /// Which prints:
+    ///
+    /// ```text
+    /// Allocated:   2100 bytes in 2 allocations
+    /// Occupied:    3024 bytes (30.6% waste)
+    /// Blocks:      1024, 2000
+    /// Oversize:    1 block, with 2000 bytes
+    /// Peak:        2100 bytes allocated, 2 blocks
+    /// ```
+    pub fn report(&self) -> String {
+        use std::fmt::Write;
+
+        let stats = self.stats();
+        let waste = if stats.occupied == 0 {
+            0.0
+        } else {
+            (stats.occupied - stats.allocated) as f64 * 100.0 / stats.occupied as f64
+        };
+        let block_sizes: Vec<String> = self
+            .blocks
+            .borrow()
+            .iter()
+            .map(|block| block.capacity().to_string())
+            .collect();
+
+        fn count(n: u64, noun: &str) -> String {
+            if n == 1 {
+                format!("1 {}", noun)
+            } else {
+                format!("{} {}s", n, noun)
+            }
+        }
+
+        let mut report = String::new();
+        let _ = writeln!(
+            report,
+            "Allocated:   {} in {}",
+            count(stats.allocated as u64, "byte"),
+            count(stats.alloc_count, "allocation")
+        );
+        let _ = writeln!(
+            report,
+            "Occupied:    {} ({:.1}% waste)",
+            count(stats.occupied as u64, "byte"),
+            waste
+        );
+        let _ = writeln!(report, "Blocks:      {}", block_sizes.join(", "));
+        let _ = writeln!(
+            report,
+            "Oversize:    {}, with {}",
+            count(stats.oversize_block_count as u64, "block"),
+            count(stats.oversize_allocated as u64, "byte")
+        );
+        let _ = writeln!(
+            report,
+            "Peak:        {} allocated, {}",
+            count(stats.peak_allocated as u64, "byte"),
+            count(stats.peak_block_count as u64, "block")
+        );
+        report
     }
+}
 
-    // /// Returns statistics about the current usage as a tuple:
-    // /// (space occupied, space allocated, block count, large block count)
-    // ///
-    // /// Space occupied is the amount of real memory that the Arena
-    // /// is taking up (not counting book keeping).
-    // ///
-    // /// Space allocated is the amount of occupied space that is
-    // /// actually used.  In other words, it is the sum of the all the
-    // /// allocation requests made to the arena by client code.
-    // ///
-    // /// Block count is the number of blocks that have been allocated.
-    // pub fn stats(&self) -> (usize, usize, usize) {
-    //     let occupied = self.stat_space_occupied.get();
-    //     let allocated = self.stat_space_allocated.get();
-    //     let blocks = self.blocks.borrow().len();
-
-    //     (occupied, allocated, blocks)
-    // }
+/// A snapshot of an arena's allocation state, for rolling back to.
+pub(crate) struct Checkpoint {
+    /// The current block and its length.
+    front: Option<(*const MaybeUninit<u8>, usize)>,
+    /// The last one-off block, if any.
+    back: Option<*const MaybeUninit<u8>>,
+    drop_mark: DropMark,
+    handle_count: usize,
+    stat_space_occupied: usize,
+    stat_space_allocated: usize,
+    stat_oversize_block_count: usize,
+    stat_oversize_allocated: usize,
 }
 
 /// Strategy for determining the size of new blocks.
 ///
 /// - `Constant`: no growth.  All blocks are the same size.
 /// - `Percentage`: block size is determined as a percentage of the current
-///                 total arena size, with the configured block size as a
-///                 minimum.  Recommended values are between 10 and 50 percent.
+///   total arena size, with the configured block size as a minimum.
+///   Recommended values are between 10 and 50 percent.
 ///
 /// For most use cases `Constant` is recommended.
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
 pub enum GrowthStrategy {
+    #[default]
     Constant,
     Percentage(u8),
 }
 
-impl Default for GrowthStrategy {
-    fn default() -> GrowthStrategy {
-        GrowthStrategy::Constant
-    }
+/// Policy for which blocks `clear()` keeps for reuse by later allocations.
+///
+/// - `Nothing`: all blocks are freed.
+/// - `LargestBlock`: the largest block is kept.
+/// - `UpToBytes(n)`: the largest blocks whose combined capacity is at most
+///   `n` bytes are kept.
+/// - `Everything`: all blocks are kept, the same as `reset()`.
+///
+/// Keeping blocks trades a larger memory footprint for not having to
+/// allocate them again.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum RetentionPolicy {
+    #[default]
+    Nothing,
+    LargestBlock,
+    UpToBytes(usize),
+    Everything,
+}
+
+/// The number of bytes needed to pad `addr` up to a multiple of `alignment`.
+#[inline(always)]
+fn alignment_offset(addr: usize, alignment: usize) -> usize {
+    (alignment - (addr % alignment)) % alignment
+}
+
+/// `part` as a percentage of `whole`, without overflowing for large sizes.
+#[inline(always)]
+fn percentage(part: usize, whole: usize) -> usize {
+    (part as u128 * 100 / whole as u128) as usize
+}
+
+/// What to do when the backing allocator fails to allocate a block for one
+/// of the infallible allocation methods.
+///
+/// - `Abort`: call `std::alloc::handle_alloc_error()`, which aborts the
+///   process unless a custom handler has been installed.
+/// - `Panic`: panic, so that the failure can be caught with
+///   `std::panic::catch_unwind()`.
+/// - `Handler(f)`: call `f` with the layout of the block that couldn't be
+///   allocated.
+///
+/// The `try_*` methods return an error regardless of the policy.
+#[derive(Debug, Copy, Clone, Default)]
+pub enum OomPolicy {
+    #[default]
+    Abort,
+    Panic,
+    Handler(fn(Layout) -> !),
 }
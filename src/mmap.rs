@@ -0,0 +1,247 @@
+use std::{
+    alloc::Layout,
+    ptr::{self, NonNull},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{ArenaAllocError, BlockSource};
+
+/// The huge page size that's assumed, which is the default on x86-64 and
+/// most AArch64 Linux systems.
+const HUGE_PAGE_SIZE: usize = 2 << 20;
+
+/// A block source that maps memory directly from the operating system with
+/// `mmap()`.
+///
+/// Only available on Unix, with the `mmap` feature.  Blocks are rounded up
+/// to a whole number of pages, and their memory is returned to the
+/// operating system as soon as they're freed, which makes this a good fit
+/// for very large arenas.
+///
+/// ```rust
+/// # use kioku::{Arena, HugePages, MmapSource};
+/// let arena = Arena::new()
+///     .with_block_size(1 << 20)
+///     .with_block_source(MmapSource::new().with_huge_pages(HugePages::Transparent));
+/// let data = arena.alloc_array(1u64, 1000);
+/// assert_eq!(1000, data.iter().sum::<u64>());
+/// ```
+#[derive(Debug, Copy, Clone, Default)]
+pub struct MmapSource {
+    huge_pages: HugePages,
+}
+
+impl MmapSource {
+    /// Creates a block source that maps memory with normal-sized pages.
+    pub fn new() -> MmapSource {
+        MmapSource {
+            huge_pages: HugePages::Never,
+        }
+    }
+
+    /// Build a block source that uses huge pages as specified.
+    ///
+    /// Huge pages reduce TLB misses when accessing large amounts of memory.
+    /// They're only supported on Linux, and elsewhere this has no effect.
+    pub fn with_huge_pages(self, huge_pages: HugePages) -> MmapSource {
+        MmapSource { huge_pages }
+    }
+}
+
+unsafe impl BlockSource for MmapSource {
+    fn acquire(&self, layout: Layout) -> Result<NonNull<[u8]>, ArenaAllocError> {
+        #[cfg(target_os = "linux")]
+        if self.huge_pages == HugePages::Explicit {
+            // Fall back to normal pages if there aren't any huge pages
+            // available.
+            if let Some(memory) = map_aligned(layout, HUGE_PAGE_SIZE, libc::MAP_HUGETLB)? {
+                return Ok(memory);
+            }
+        }
+
+        // Transparent huge pages are only used for the parts of a mapping
+        // that are aligned to the huge page size, so big enough blocks are
+        // aligned to it.
+        let transparent = self.huge_pages == HugePages::Transparent
+            && cfg!(target_os = "linux")
+            && layout.size() >= HUGE_PAGE_SIZE;
+        let map_layout = if transparent {
+            layout.align_to(HUGE_PAGE_SIZE).unwrap()
+        } else {
+            layout
+        };
+
+        let memory =
+            map_aligned(map_layout, page_size(), 0)?.ok_or(ArenaAllocError::OutOfMemory(layout))?;
+
+        #[cfg(target_os = "linux")]
+        if transparent {
+            unsafe {
+                libc::madvise(
+                    memory.as_ptr() as *mut libc::c_void,
+                    memory.len(),
+                    libc::MADV_HUGEPAGE,
+                );
+            }
+        }
+
+        Ok(memory)
+    }
+
+    unsafe fn release(&self, ptr: NonNull<u8>, layout: Layout) {
+        // The size was already rounded to the right page size by `acquire()`.
+        unmap(ptr.as_ptr(), layout.size());
+    }
+}
+
+/// How `MmapSource` uses huge pages.
+///
+/// - `Never`: only normal-sized pages are used.
+/// - `Transparent`: the kernel is advised to back large blocks with
+///   transparent huge pages where it can.  Requires transparent huge pages
+///   to be enabled, at least in `madvise` mode.
+/// - `Explicit`: blocks are mapped from the pool of reserved huge pages,
+///   and rounded up to a multiple of the huge page size.  Falls back to
+///   normal pages if the pool is exhausted or hasn't been set up.
+///
+/// Huge pages are assumed to be 2 MiB.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum HugePages {
+    #[default]
+    Never,
+    Transparent,
+    Explicit,
+}
+
+/// A block source that places each block between two inaccessible guard
+/// pages, for `Arena::with_guard_pages()`.
+///
+/// The end of the block is flush against the guard page after it.  Only
+/// alignments up to the page size are supported.
+#[cfg(feature = "guard_pages")]
+pub(crate) struct GuardedSource;
+
+#[cfg(feature = "guard_pages")]
+unsafe impl BlockSource for GuardedSource {
+    fn acquire(&self, layout: Layout) -> Result<NonNull<[u8]>, ArenaAllocError> {
+        let page_size = page_size();
+        debug_assert!(layout.align() <= page_size);
+        let data_size =
+            round_up(layout.size(), page_size).ok_or(ArenaAllocError::LayoutOverflow)?;
+        let mapped_size = data_size
+            .checked_add(page_size * 2)
+            .ok_or(ArenaAllocError::LayoutOverflow)?;
+
+        let mapped = unsafe { map(mapped_size, 0) }.ok_or(ArenaAllocError::OutOfMemory(layout))?;
+        let protected = unsafe {
+            libc::mprotect(mapped as *mut libc::c_void, page_size, libc::PROT_NONE) == 0
+                && libc::mprotect(
+                    mapped.add(page_size + data_size) as *mut libc::c_void,
+                    page_size,
+                    libc::PROT_NONE,
+                ) == 0
+        };
+        if !protected {
+            // Failing is better than silently going without guard pages.
+            unsafe { unmap(mapped, mapped_size) };
+            return Err(ArenaAllocError::OutOfMemory(layout));
+        }
+
+        // Rounding the size up to the alignment keeps the start aligned.
+        let guard_addr = mapped as usize + page_size + data_size;
+        let start = guard_addr - round_up(layout.size(), layout.align()).unwrap();
+        Ok(NonNull::slice_from_raw_parts(
+            unsafe { NonNull::new_unchecked(start as *mut u8) },
+            layout.size(),
+        ))
+    }
+
+    unsafe fn release(&self, ptr: NonNull<u8>, layout: Layout) {
+        let page_size = page_size();
+        let guard_addr = round_up(ptr.as_ptr() as usize + layout.size(), page_size).unwrap();
+        let data_size = round_up(layout.size(), page_size).unwrap();
+        unmap(
+            (guard_addr - data_size - page_size) as *mut u8,
+            data_size + page_size * 2,
+        );
+    }
+}
+
+/// The operating system's page size.
+pub(crate) fn page_size() -> usize {
+    static PAGE_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+    let mut size = PAGE_SIZE.load(Ordering::Relaxed);
+    if size == 0 {
+        size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        PAGE_SIZE.store(size, Ordering::Relaxed);
+    }
+    size
+}
+
+/// Rounds `n` up to a multiple of `align`, which must be a power of two.
+#[inline(always)]
+fn round_up(n: usize, align: usize) -> Option<usize> {
+    Some(n.checked_add(align - 1)? & !(align - 1))
+}
+
+/// Maps enough memory for `layout` with pages of `page_size` bytes, and the
+/// extra `flags`.  Returns `None` if the memory couldn't be mapped.
+fn map_aligned(
+    layout: Layout,
+    page_size: usize,
+    flags: libc::c_int,
+) -> Result<Option<NonNull<[u8]>>, ArenaAllocError> {
+    let size = round_up(layout.size(), page_size).ok_or(ArenaAllocError::LayoutOverflow)?;
+
+    // Mappings are always page aligned, so bigger alignments need some
+    // extra room to align within, which is unmapped afterwards.
+    let extra = layout.align().saturating_sub(page_size);
+    let mapped_size = size
+        .checked_add(extra)
+        .ok_or(ArenaAllocError::LayoutOverflow)?;
+    let mapped = match unsafe { map(mapped_size, flags) } {
+        Some(mapped) => mapped,
+        None => return Ok(None),
+    };
+
+    let addr = mapped as usize;
+    let aligned = round_up(addr, layout.align()).unwrap();
+    unsafe {
+        if aligned > addr {
+            unmap(mapped, aligned - addr);
+        }
+        if addr + mapped_size > aligned + size {
+            unmap(
+                (aligned + size) as *mut u8,
+                addr + mapped_size - aligned - size,
+            );
+        }
+    }
+
+    Ok(Some(NonNull::slice_from_raw_parts(
+        unsafe { NonNull::new_unchecked(aligned as *mut u8) },
+        size,
+    )))
+}
+
+/// Maps `size` bytes of zeroed, readable and writable memory.
+unsafe fn map(size: usize, flags: libc::c_int) -> Option<*mut u8> {
+    let ptr = libc::mmap(
+        ptr::null_mut(),
+        size,
+        libc::PROT_READ | libc::PROT_WRITE,
+        libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | flags,
+        -1,
+        0,
+    );
+    if ptr == libc::MAP_FAILED {
+        None
+    } else {
+        Some(ptr as *mut u8)
+    }
+}
+
+unsafe fn unmap(ptr: *mut u8, size: usize) {
+    libc::munmap(ptr as *mut libc::c_void, size);
+}
@@ -0,0 +1,145 @@
+use std::{fmt, marker::PhantomData, ptr};
+
+/// A pointer that stores the offset to its target from its own address,
+/// rather than the target's absolute address.
+///
+/// This makes data structures built from them position-independent: as long
+/// as a pointer and its target are moved together (e.g. by copying a whole
+/// arena block with `memcpy`, or writing it to disk and reading it back) the
+/// pointer remains valid.  A null pointer is represented by an offset of
+/// zero.
+///
+/// ```rust
+/// # use kioku::{Arena, ArenaOffsetPtr};
+/// #[derive(Copy, Clone)]
+/// struct Node {
+///     value: u32,
+///     next: ArenaOffsetPtr<Node>,
+/// }
+///
+/// let arena = Arena::new();
+/// let nodes = arena.alloc_array(Node { value: 0, next: ArenaOffsetPtr::null() }, 2);
+/// nodes[1].value = 1;
+/// let (first, second) = nodes.split_at_mut(1);
+/// first[0].next.set(&second[0]);
+///
+/// // Copying both nodes together keeps the pointer valid.
+/// let copied = arena.copy_slice(nodes);
+/// assert_eq!(Some(1), unsafe { copied[0].next.as_ref() }.map(|n| n.value));
+/// ```
+///
+/// Since the pointer is relative to its own address, moving or copying it
+/// *without* its target makes it point to the wrong place.  Hence
+/// dereferencing it is unsafe.
+#[repr(transparent)]
+pub struct ArenaOffsetPtr<T> {
+    offset: isize,
+    _phantom: PhantomData<*const T>,
+}
+
+// Same as for `&T`.
+unsafe impl<T: Sync> Send for ArenaOffsetPtr<T> {}
+unsafe impl<T: Sync> Sync for ArenaOffsetPtr<T> {}
+
+impl<T> ArenaOffsetPtr<T> {
+    /// A null pointer.
+    #[inline]
+    pub const fn null() -> ArenaOffsetPtr<T> {
+        ArenaOffsetPtr {
+            offset: 0,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Whether the pointer is null.
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        self.offset == 0
+    }
+
+    /// Points the pointer at `target`.
+    ///
+    /// `target` mustn't be at the pointer's own address, such as when it's
+    /// the first field of a `#[repr(C)]` struct pointing at itself, since
+    /// that would make the pointer null.  This is checked in debug builds.
+    #[inline]
+    pub fn set(&mut self, target: &T) {
+        debug_assert!(
+            !ptr::eq(target as *const T as *const Self, self),
+            "ArenaOffsetPtr can't point at its own address"
+        );
+        self.set_ptr(target);
+    }
+
+    /// Points the pointer at `target`, or makes it null if `target` is null.
+    ///
+    /// Note that a pointer can't point at itself, since that's the null
+    /// representation.
+    #[inline]
+    pub fn set_ptr(&mut self, target: *const T) {
+        self.offset = if target.is_null() {
+            0
+        } else {
+            (target as isize).wrapping_sub(self as *const Self as isize)
+        };
+    }
+
+    /// The byte offset from the pointer's own address to its target.
+    #[inline]
+    pub fn offset(&self) -> isize {
+        self.offset
+    }
+
+    /// The absolute address of the target, or null.
+    #[inline]
+    pub fn as_ptr(&self) -> *const T {
+        if self.offset == 0 {
+            ptr::null()
+        } else {
+            (self as *const Self as isize).wrapping_add(self.offset) as *const T
+        }
+    }
+
+    /// A reference to the target, or `None` if the pointer is null.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must point to a valid `T` from its current location,
+    /// with the same requirements as dereferencing a raw pointer.
+    #[inline]
+    pub unsafe fn as_ref(&self) -> Option<&T> {
+        self.as_ptr().as_ref()
+    }
+
+    /// A mutable reference to the target, or `None` if the pointer is null.
+    ///
+    /// # Safety
+    ///
+    /// Same as `as_ref()`, and the target must not be aliased.
+    #[inline]
+    pub unsafe fn as_mut(&mut self) -> Option<&mut T> {
+        (self.as_ptr() as *mut T).as_mut()
+    }
+}
+
+impl<T> Clone for ArenaOffsetPtr<T> {
+    #[inline]
+    fn clone(&self) -> ArenaOffsetPtr<T> {
+        *self
+    }
+}
+
+impl<T> Copy for ArenaOffsetPtr<T> {}
+
+impl<T> Default for ArenaOffsetPtr<T> {
+    #[inline]
+    fn default() -> ArenaOffsetPtr<T> {
+        ArenaOffsetPtr::null()
+    }
+}
+
+impl<T> fmt::Debug for ArenaOffsetPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ArenaOffsetPtr").field(&self.offset).finish()
+    }
+}
@@ -0,0 +1,20 @@
+use std::mem::{transmute, MaybeUninit};
+
+use rayon::prelude::*;
+
+/// Initializes each element of `memory` to the return value of `f` called
+/// with that element's index, in parallel.
+///
+/// If `f` panics, the partially initialized array is simply abandoned, and
+/// is never exposed.
+pub(crate) fn fill<T, F>(memory: &mut [MaybeUninit<T>], f: F) -> &mut [T]
+where
+    T: Copy + Send,
+    F: Fn(usize) -> T + Sync + Send,
+{
+    memory.par_iter_mut().enumerate().for_each(|(i, v)| {
+        v.write(f(i));
+    });
+
+    unsafe { transmute(memory) }
+}
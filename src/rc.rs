@@ -0,0 +1,183 @@
+use std::{
+    borrow,
+    cell::Cell,
+    cmp::Ordering,
+    fmt, hash,
+    marker::PhantomData,
+    ops,
+    ptr::{self, NonNull},
+};
+
+use allocator_api2::alloc::Allocator;
+
+use crate::Arena;
+
+/// A reference-counted pointer to a value stored in an arena.
+///
+/// This works much like `std::rc::Rc`, but both the reference count and the
+/// value are stored in the arena.  The value is dropped when the last
+/// `ARc` pointing to it is dropped, but the memory itself is only reclaimed
+/// when the arena is cleared or dropped.
+///
+/// ```rust
+/// # use kioku::{ARc, Arena};
+/// let arena = Arena::new();
+/// let a = ARc::new_in(String::from("Hello"), &arena);
+/// let b = ARc::clone(&a);
+/// assert_eq!(2, ARc::strong_count(&a));
+/// assert!(ARc::ptr_eq(&a, &b));
+///
+/// drop(a);
+/// assert_eq!(Ok(String::from("Hello")), ARc::try_unwrap(b));
+/// ```
+pub struct ARc<'a, T> {
+    ptr: NonNull<RcBox<T>>,
+    _phantom: PhantomData<(&'a RcBox<T>, T)>,
+}
+
+struct RcBox<T> {
+    count: Cell<usize>,
+    value: T,
+}
+
+impl<'a, T> ARc<'a, T> {
+    /// Moves `value` into the arena, and returns a pointer to it.
+    pub fn new_in<A: Allocator + Clone>(value: T, arena: &'a Arena<A>) -> ARc<'a, T> {
+        let memory = arena.alloc_uninit::<RcBox<T>>();
+        unsafe {
+            memory.as_mut_ptr().write(RcBox {
+                count: Cell::new(1),
+                value,
+            });
+            ARc {
+                ptr: NonNull::new_unchecked(memory.as_mut_ptr()),
+                _phantom: PhantomData,
+            }
+        }
+    }
+
+    /// The number of `ARc`s pointing to the same value.
+    #[inline]
+    pub fn strong_count(this: &ARc<'a, T>) -> usize {
+        this.inner().count.get()
+    }
+
+    /// Whether two `ARc`s point to the same value.
+    #[inline]
+    pub fn ptr_eq(this: &ARc<'a, T>, other: &ARc<'a, T>) -> bool {
+        this.ptr == other.ptr
+    }
+
+    /// A mutable reference to the value, if there are no other `ARc`s
+    /// pointing to it.
+    #[inline]
+    pub fn get_mut<'b>(this: &'b mut ARc<'a, T>) -> Option<&'b mut T> {
+        if ARc::strong_count(this) == 1 {
+            Some(unsafe { &mut (*this.ptr.as_ptr()).value })
+        } else {
+            None
+        }
+    }
+
+    /// Moves the value out, if there are no other `ARc`s pointing to it.
+    /// Otherwise returns the `ARc` as an error.
+    pub fn try_unwrap(this: ARc<'a, T>) -> Result<T, ARc<'a, T>> {
+        if ARc::strong_count(&this) != 1 {
+            return Err(this);
+        }
+        let value = unsafe { ptr::addr_of!((*this.ptr.as_ptr()).value).read() };
+        std::mem::forget(this);
+        Ok(value)
+    }
+
+    #[inline(always)]
+    fn inner(&self) -> &RcBox<T> {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<'a, T> Clone for ARc<'a, T> {
+    #[inline]
+    fn clone(&self) -> ARc<'a, T> {
+        let count = &self.inner().count;
+        count.set(count.get().checked_add(1).expect("ARc count overflow"));
+        ARc {
+            ptr: self.ptr,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Drop for ARc<'a, T> {
+    fn drop(&mut self) {
+        let count = &self.inner().count;
+        count.set(count.get() - 1);
+        if count.get() == 0 {
+            unsafe { ptr::drop_in_place(ptr::addr_of_mut!((*self.ptr.as_ptr()).value)) };
+        }
+    }
+}
+
+impl<'a, T> ops::Deref for ARc<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.inner().value
+    }
+}
+
+impl<'a, T> AsRef<T> for ARc<'a, T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T> borrow::Borrow<T> for ARc<'a, T> {
+    #[inline]
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, 'b, T: PartialEq> PartialEq<ARc<'b, T>> for ARc<'a, T> {
+    #[inline]
+    fn eq(&self, other: &ARc<'b, T>) -> bool {
+        **self == **other
+    }
+}
+
+impl<'a, T: Eq> Eq for ARc<'a, T> {}
+
+impl<'a, 'b, T: PartialOrd> PartialOrd<ARc<'b, T>> for ARc<'a, T> {
+    #[inline]
+    fn partial_cmp(&self, other: &ARc<'b, T>) -> Option<Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<'a, T: Ord> Ord for ARc<'a, T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<'a, T: hash::Hash> hash::Hash for ARc<'a, T> {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for ARc<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<'a, T: fmt::Display> fmt::Display for ARc<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
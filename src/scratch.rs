@@ -0,0 +1,93 @@
+use std::{cell::Cell, fmt, marker::PhantomData, ops};
+
+use crate::Arena;
+
+thread_local! {
+    static SCRATCH: Arena = Arena::new();
+
+    /// The number of live `ScratchArena`s on this thread.
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A handle to the current thread's scratch arena.
+///
+/// Created by `thread_local_arena()`.  Allocations borrow from the handle,
+/// and the arena is automatically cleared when the outermost handle on the
+/// thread is dropped.  Nested handles share the same arena, and don't clear
+/// it when dropped, so allocations made through an outer handle stay valid.
+///
+/// ```rust
+/// let scratch = kioku::thread_local_arena();
+/// let text = scratch.copy_str("Hello");
+/// {
+///     let inner = kioku::thread_local_arena();
+///     inner.alloc_array(0u8, 100);
+/// }
+/// assert_eq!("Hello", text);
+/// ```
+pub struct ScratchArena {
+    arena: *const Arena,
+    // Not `Send`, since the arena belongs to this thread.
+    _phantom: PhantomData<*const Arena>,
+}
+
+/// Gets a handle to the current thread's scratch arena, which is cleared
+/// when the last handle is dropped.
+///
+/// See `ScratchArena` for details.
+pub fn thread_local_arena() -> ScratchArena {
+    DEPTH.with(|depth| depth.set(depth.get() + 1));
+    ScratchArena {
+        arena: SCRATCH.with(|arena| arena as *const Arena),
+        _phantom: PhantomData,
+    }
+}
+
+/// Runs `f` with the current thread's scratch arena.
+///
+/// Nothing allocated in `f` can escape it.  The arena is cleared when `f`
+/// returns, unless this call is nested inside another use of the scratch
+/// arena, in which case the outermost one clears it.
+///
+/// ```rust
+/// let sum = kioku::with_scratch(|arena| {
+///     let values = arena.alloc_array_with(100, |i| i as u32);
+///     values.iter().sum::<u32>()
+/// });
+/// assert_eq!(4950, sum);
+/// ```
+pub fn with_scratch<R, F: FnOnce(&Arena) -> R>(f: F) -> R {
+    f(&thread_local_arena())
+}
+
+impl ops::Deref for ScratchArena {
+    type Target = Arena;
+
+    #[inline]
+    fn deref(&self) -> &Arena {
+        // Safe because the thread-local arena outlives any handle to it on
+        // this thread.
+        unsafe { &*self.arena }
+    }
+}
+
+impl Drop for ScratchArena {
+    fn drop(&mut self) {
+        let depth = DEPTH.with(|depth| {
+            depth.set(depth.get() - 1);
+            depth.get()
+        });
+        if depth == 0 {
+            // Safe because no other handles exist on this thread, and the
+            // borrows of this one have ended, so nothing refers to the
+            // arena's memory anymore.
+            unsafe { (*self.arena).clear_unchecked() };
+        }
+    }
+}
+
+impl fmt::Debug for ScratchArena {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
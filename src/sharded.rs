@@ -0,0 +1,163 @@
+use std::{
+    cell::Cell,
+    collections::LinkedList,
+    fmt, ptr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, MutexGuard,
+    },
+    thread::{self, ThreadId},
+};
+
+use crate::Arena;
+
+/// An arena made up of one independent shard per thread.
+///
+/// Each thread allocates from its own `Arena` (obtained with `local()`), so
+/// allocation is never contended and the full `Arena` API is available.
+/// All of the shards share the lifetime of the `ShardedArena` itself, so
+/// references allocated by one thread can be freely shared with other
+/// threads afterwards.  The shards are cleared and dropped together.
+///
+/// ```rust
+/// # use kioku::ShardedArena;
+/// let arena = ShardedArena::new();
+/// let strings: Vec<&str> = std::thread::scope(|s| {
+///     let handles: Vec<_> = (0..4)
+///         .map(|i| {
+///             let arena = &arena;
+///             s.spawn(move || &*arena.local().alloc_fmt(format_args!("Thread {}", i)))
+///         })
+///         .collect();
+///     handles.into_iter().map(|h| h.join().unwrap()).collect()
+/// });
+/// assert_eq!("Thread 2", strings[2]);
+/// assert_eq!(4, arena.shard_count());
+/// ```
+pub struct ShardedArena {
+    /// Unique for every arena and every `clear()`, so that stale entries in
+    /// the thread-local shard cache are never mistaken for valid ones.
+    id: usize,
+    // Linked list nodes never move, so the shards can be pointed at while
+    // other threads add their own.
+    shards: Mutex<LinkedList<(ThreadId, Arena)>>,
+    block_size: usize,
+}
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+thread_local! {
+    /// The shard most recently used by this thread, and the id of the
+    /// `ShardedArena` it belongs to.
+    static CACHED_SHARD: Cell<(usize, *const Arena)> = const { Cell::new((0, ptr::null())) };
+}
+
+impl Default for ShardedArena {
+    fn default() -> ShardedArena {
+        ShardedArena::new()
+    }
+}
+
+impl fmt::Debug for ShardedArena {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The shards themselves can't be inspected, since they may be in use
+        // by other threads.
+        f.debug_struct("ShardedArena")
+            .field("shard_count()", &self.shard_count())
+            .field("block_size", &self.block_size)
+            .finish()
+    }
+}
+
+impl ShardedArena {
+    /// Create a new sharded arena, whose shards have default settings.
+    pub fn new() -> ShardedArena {
+        ShardedArena {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            shards: Mutex::new(LinkedList::new()),
+            block_size: Arena::new().min_block_size,
+        }
+    }
+
+    /// Build a sharded arena whose shards have the specified block size in
+    /// bytes.
+    pub fn with_block_size(self, block_size: usize) -> ShardedArena {
+        assert!(
+            block_size > 0,
+            "Initial block size must be greater \
+             than zero"
+        );
+        assert!(
+            self.lock().is_empty(),
+            "Cannot change initial block size after \
+             shards have already been created"
+        );
+
+        ShardedArena { block_size, ..self }
+    }
+
+    /// The current thread's shard.
+    ///
+    /// The shard is created the first time a thread asks for it.
+    #[inline]
+    pub fn local(&self) -> &Arena {
+        if let Ok((id, shard)) = CACHED_SHARD.try_with(|cache| cache.get()) {
+            if id == self.id {
+                // Safe because the shard lives until the arena is cleared or
+                // dropped, both of which change or retire `self.id`.
+                return unsafe { &*shard };
+            }
+        }
+        self.local_slow()
+    }
+
+    #[cold]
+    fn local_slow(&self) -> &Arena {
+        let thread = thread::current().id();
+        let mut shards = self.lock();
+
+        let shard: *const Arena = match shards.iter().find(|(t, _)| *t == thread) {
+            Some((_, shard)) => shard,
+            None => {
+                let shard = Arena::new().with_block_size(self.block_size);
+                shards.push_back((thread, shard));
+                &shards.back().unwrap().1
+            }
+        };
+        let _ = CACHED_SHARD.try_with(|cache| cache.set((self.id, shard)));
+
+        // Safe because the shard only moves or is freed on `clear()` or drop,
+        // and `Arena` isn't `Sync`, so the reference can't leave this thread.
+        unsafe { &*shard }
+    }
+
+    /// The number of threads that have allocated from the arena.
+    pub fn shard_count(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Mutable access to all of the shards, e.g. for gathering statistics.
+    pub fn shards_mut(&mut self) -> impl Iterator<Item = &mut Arena> {
+        self.shards
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter_mut()
+            .map(|(_, shard)| shard)
+    }
+
+    /// Frees all memory currently allocated by the arena, in all shards.
+    pub fn clear(&mut self) {
+        self.shards
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+        self.id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    fn lock(&self) -> MutexGuard<'_, LinkedList<(ThreadId, Arena)>> {
+        // A panic while locked can't leave the shard list in an inconsistent
+        // state, so poisoning is ignored.
+        self.shards.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    alloc::{self, Layout},
+    fmt,
+    mem::MaybeUninit,
+    ptr::NonNull,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::ArenaAllocError;
+
+/// Where an arena gets its blocks of memory from.
+///
+/// Selected with `Arena::with_block_source()`.  This is separate from the
+/// arena's bump allocation, which carves individual allocations out of the
+/// blocks, so it's only called when the arena needs a new block or frees
+/// one.  By default blocks come from the global allocator (`HeapSource`).
+///
+/// ```rust
+/// # use kioku::{Arena, ArenaAllocError, BlockSource, HeapSource};
+/// use std::alloc::Layout;
+/// use std::ptr::NonNull;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// /// Counts the bytes handed out to arenas.
+/// struct Counted(AtomicUsize);
+///
+/// unsafe impl BlockSource for Counted {
+///     fn acquire(&self, layout: Layout) -> Result<NonNull<[u8]>, ArenaAllocError> {
+///         self.0.fetch_add(layout.size(), Ordering::Relaxed);
+///         HeapSource.acquire(layout)
+///     }
+///
+///     unsafe fn release(&self, ptr: NonNull<u8>, layout: Layout) {
+///         HeapSource.release(ptr, layout)
+///     }
+/// }
+///
+/// static SOURCE: Counted = Counted(AtomicUsize::new(0));
+///
+/// let arena = Arena::new().with_block_size(256).with_block_source(&SOURCE);
+/// arena.alloc(1u32);
+/// assert_eq!(256, SOURCE.0.load(Ordering::Relaxed));
+/// ```
+///
+/// # Safety
+///
+/// On success, `acquire()` must return memory that's at least as big as
+/// `layout.size()`, aligned to `layout.align()`, and that stays valid and
+/// unused by anything else until it's passed to `release()`.
+pub unsafe trait BlockSource: Send + Sync {
+    /// Allocates memory for a block.
+    ///
+    /// The memory returned may be bigger than requested, in which case the
+    /// arena uses all of it.
+    fn acquire(&self, layout: Layout) -> Result<NonNull<[u8]>, ArenaAllocError>;
+
+    /// Frees memory previously returned by `acquire()`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by `acquire()` on this source, and
+    /// `layout` must have the alignment that was requested and the size of
+    /// the memory that was returned.
+    unsafe fn release(&self, ptr: NonNull<u8>, layout: Layout);
+}
+
+unsafe impl<S: BlockSource + ?Sized> BlockSource for &S {
+    #[inline]
+    fn acquire(&self, layout: Layout) -> Result<NonNull<[u8]>, ArenaAllocError> {
+        (**self).acquire(layout)
+    }
+
+    #[inline]
+    unsafe fn release(&self, ptr: NonNull<u8>, layout: Layout) {
+        (**self).release(ptr, layout)
+    }
+}
+
+/// A block source that uses the global allocator.
+///
+/// This is the default.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct HeapSource;
+
+unsafe impl BlockSource for HeapSource {
+    #[inline]
+    fn acquire(&self, layout: Layout) -> Result<NonNull<[u8]>, ArenaAllocError> {
+        let ptr = NonNull::new(unsafe { alloc::alloc(layout) })
+            .ok_or(ArenaAllocError::OutOfMemory(layout))?;
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    #[inline]
+    unsafe fn release(&self, ptr: NonNull<u8>, layout: Layout) {
+        alloc::dealloc(ptr.as_ptr(), layout);
+    }
+}
+
+/// A block source that hands out blocks from a fixed buffer, and fails
+/// once the buffer is used up.
+///
+/// Useful where the global allocator can't or shouldn't be used.  Like an
+/// arena, only the most recently acquired block can be given back for
+/// reuse, so this works best with arenas that don't free and re-acquire
+/// blocks, i.e. that retain their blocks when cleared.
+///
+/// ```rust
+/// # use kioku::{Arena, BufferSource, RetentionPolicy};
+/// use std::mem::MaybeUninit;
+///
+/// static mut BUFFER: [MaybeUninit<u8>; 4096] = [MaybeUninit::uninit(); 4096];
+///
+/// let source = BufferSource::new(unsafe { &mut *std::ptr::addr_of_mut!(BUFFER) });
+/// let arena = Arena::new()
+///     .with_block_size(1024)
+///     .with_retention_policy(RetentionPolicy::Everything)
+///     .with_block_source(source);
+/// assert!(arena.try_alloc_array(0u8, 3000).is_ok());
+/// assert!(arena.try_alloc_array(0u8, 3000).is_err());
+/// ```
+pub struct BufferSource {
+    start: NonNull<MaybeUninit<u8>>,
+    len: usize,
+    used: AtomicUsize,
+}
+
+// The buffer is borrowed exclusively, and the parts of it that have been
+// handed out are tracked atomically.
+unsafe impl Send for BufferSource {}
+unsafe impl Sync for BufferSource {}
+
+impl BufferSource {
+    /// Creates a block source that uses `buffer` for its blocks.
+    pub fn new(buffer: &'static mut [MaybeUninit<u8>]) -> BufferSource {
+        BufferSource {
+            start: NonNull::new(buffer.as_mut_ptr()).unwrap(),
+            len: buffer.len(),
+            used: AtomicUsize::new(0),
+        }
+    }
+
+    /// The number of bytes of the buffer that haven't been handed out.
+    pub fn remaining(&self) -> usize {
+        self.len - self.used.load(Ordering::Relaxed)
+    }
+}
+
+unsafe impl BlockSource for BufferSource {
+    fn acquire(&self, layout: Layout) -> Result<NonNull<[u8]>, ArenaAllocError> {
+        let base = self.start.as_ptr() as usize;
+        let mut used = self.used.load(Ordering::Relaxed);
+        loop {
+            let padding = (base + used).wrapping_neg() & (layout.align() - 1);
+            let start = used + padding;
+            if layout.size() > self.len.saturating_sub(start) {
+                return Err(ArenaAllocError::OutOfMemory(layout));
+            }
+            match self.used.compare_exchange_weak(
+                used,
+                start + layout.size(),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    let ptr = unsafe { self.start.as_ptr().add(start) as *mut u8 };
+                    return Ok(NonNull::slice_from_raw_parts(
+                        unsafe { NonNull::new_unchecked(ptr) },
+                        layout.size(),
+                    ));
+                }
+                Err(actual) => used = actual,
+            }
+        }
+    }
+
+    unsafe fn release(&self, ptr: NonNull<u8>, layout: Layout) {
+        // Only the most recent block can be given back.
+        let start = ptr.as_ptr() as usize - self.start.as_ptr() as usize;
+        let _ = self.used.compare_exchange(
+            start + layout.size(),
+            start,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        );
+    }
+}
+
+impl fmt::Debug for BufferSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BufferSource")
+            .field("len", &self.len)
+            .field("remaining()", &self.remaining())
+            .finish()
+    }
+}
@@ -0,0 +1,90 @@
+use std::{fmt, mem::size_of, ops, ptr::NonNull};
+
+use allocator_api2::alloc::{Allocator, Global};
+
+use crate::Arena;
+
+/// An owning pointer to a value in an arena, whose memory is reclaimed when
+/// it's dropped, if it's the most recent allocation.
+///
+/// Created by `Arena::push()`.  Like `ABox`, this runs `T`'s destructor when
+/// dropped.  Additionally, if nothing else has been allocated from the arena
+/// since, its memory is given back to the arena.  So if boxes are dropped in
+/// the reverse order they were created in, as happens naturally with nested
+/// scopes and recursion, the arena works like a stack and doesn't grow.
+///
+/// ```rust
+/// # use kioku::Arena;
+/// fn recurse(arena: &Arena, depth: usize) -> *const [u8; 64] {
+///     let tmp = arena.push([depth as u8; 64]);
+///     if depth == 0 {
+///         &*tmp
+///     } else {
+///         recurse(arena, depth - 1)
+///     }
+/// }
+///
+/// let arena = Arena::new();
+/// let a = recurse(&arena, 10);
+/// let b = recurse(&arena, 10);
+/// // The memory was reused by the second call.
+/// assert_eq!(a, b);
+/// ```
+pub struct StackBox<'a, T, A: Allocator + Clone = Global> {
+    arena: &'a Arena<A>,
+    ptr: NonNull<T>,
+}
+
+impl<'a, T, A: Allocator + Clone> StackBox<'a, T, A> {
+    pub(crate) fn new_in(value: T, arena: &'a Arena<A>) -> StackBox<'a, T, A> {
+        let memory = arena.alloc_uninit::<T>();
+        memory.write(value);
+        StackBox {
+            arena,
+            ptr: NonNull::from(memory).cast(),
+        }
+    }
+
+    /// Moves the value out of the box, reclaiming its memory if possible.
+    pub fn pop(b: StackBox<'a, T, A>) -> T {
+        let value = unsafe { b.ptr.as_ptr().read() };
+        b.reclaim();
+        std::mem::forget(b);
+        value
+    }
+
+    #[inline(always)]
+    fn reclaim(&self) {
+        self.arena
+            .try_resize_in_place(self.ptr.as_ptr() as *const _, size_of::<T>(), 0);
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> Drop for StackBox<'a, T, A> {
+    fn drop(&mut self) {
+        unsafe { self.ptr.as_ptr().drop_in_place() };
+        self.reclaim();
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> ops::Deref for StackBox<'a, T, A> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> ops::DerefMut for StackBox<'a, T, A> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<'a, T: fmt::Debug, A: Allocator + Clone> fmt::Debug for StackBox<'a, T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
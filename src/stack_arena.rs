@@ -0,0 +1,78 @@
+use std::{fmt, mem::MaybeUninit};
+
+use crate::BufferArena;
+
+/// Fixed-size arena storage of `N` bytes, stored inline rather than on the
+/// heap.
+///
+/// This is most useful as a local variable, to give a function some
+/// arena memory on the stack.  The storage is allocated from through a
+/// `BufferArena` obtained with `arena()`, which has the full `Arena` API,
+/// and fails once the storage is used up.
+///
+/// ```rust
+/// # use kioku::StackArena;
+/// fn parse(text: &str) -> usize {
+///     let mut storage = StackArena::<1024>::new();
+///     let arena = storage.arena();
+///     let words: Vec<&str> = text
+///         .split_whitespace()
+///         .map(|word| &*arena.copy_str(word))
+///         .collect();
+///     words.len()
+/// }
+///
+/// assert_eq!(3, parse("one two three"));
+/// ```
+///
+/// Neither the storage nor the arena uses the heap, so this works where the
+/// global allocator can't be used at all (with the exceptions listed for
+/// `BufferArena`).
+///
+/// The arena borrows the storage, so the storage can't be moved while
+/// anything allocated from it is still in use.  Each call to `arena()`
+/// starts over with all `N` bytes available.  The allocation methods aren't
+/// on `StackArena` itself because an arena keeps pointers into its memory,
+/// such as to the values it needs to drop, which moving the storage would
+/// leave dangling.
+#[repr(C, align(16))]
+pub struct StackArena<const N: usize> {
+    storage: [MaybeUninit<u8>; N],
+}
+
+impl<const N: usize> StackArena<N> {
+    /// Creates uninitialized storage for an arena.
+    #[inline]
+    pub const fn new() -> StackArena<N> {
+        StackArena {
+            storage: [MaybeUninit::uninit(); N],
+        }
+    }
+
+    /// An arena that allocates from the storage.
+    ///
+    /// The storage is 16-byte aligned, so allocations with up to that
+    /// alignment don't waste any of it on padding at the start.
+    #[inline]
+    pub fn arena(&mut self) -> BufferArena<'_> {
+        BufferArena::new(&mut self.storage)
+    }
+
+    /// The size of the storage in bytes.
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<const N: usize> Default for StackArena<N> {
+    fn default() -> StackArena<N> {
+        StackArena::new()
+    }
+}
+
+impl<const N: usize> fmt::Debug for StackArena<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StackArena").field("capacity", &N).finish()
+    }
+}
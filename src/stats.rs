@@ -0,0 +1,125 @@
+use std::fmt;
+
+use allocator_api2::alloc::Allocator;
+
+use crate::{Arena, SyncArena};
+
+/// Statistics about an arena's memory usage, from `Arena::stats()`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct ArenaStats {
+    /// The amount of real memory that the arena is taking up, not counting
+    /// book keeping.
+    pub occupied: usize,
+
+    /// The amount of occupied space that is actually used.  In other words,
+    /// the sum of all the allocation requests made to the arena by client
+    /// code.
+    pub allocated: usize,
+
+    /// The number of blocks that the arena has allocated.
+    pub block_count: usize,
+
+    /// How many of those blocks were allocated for a single oversized
+    /// allocation, rather than shared between allocations.  This is also the
+    /// number of oversized allocations.
+    ///
+    /// An allocation is oversized if it's too big for a shared block, or if
+    /// starting a new shared block for it would waste too much of the
+    /// current one.  Lots of these suggest that the block size is too small.
+    pub oversize_block_count: usize,
+
+    /// The part of `allocated` that's in oversized allocations.
+    pub oversize_allocated: usize,
+
+    /// The number of allocations made since the arena was last cleared, or
+    /// since the last `Arena::reset_stats()`.
+    pub alloc_count: u64,
+
+    /// The number of allocations made since the arena was created, or since
+    /// the last `Arena::reset_stats()`.
+    pub total_alloc_count: u64,
+
+    /// The highest `allocated` has been since the arena was created, or
+    /// since the last `Arena::reset_stats()`.
+    pub peak_allocated: usize,
+
+    /// The highest `block_count` has been since the arena was created, or
+    /// since the last `Arena::reset_stats()`.
+    pub peak_block_count: usize,
+}
+
+impl fmt::Display for ArenaStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} bytes allocated in {} allocations, {} bytes occupied, {} blocks ({} oversize, with {} bytes)",
+            self.allocated,
+            self.alloc_count,
+            self.occupied,
+            self.block_count,
+            self.oversize_block_count,
+            self.oversize_allocated,
+        )
+    }
+}
+
+impl ArenaStats {
+    /// The statistics as `(name, value)` pairs, for exporting to monitoring
+    /// systems.
+    ///
+    /// The names are in `snake_case` with units where applicable, following
+    /// common metrics naming conventions.  Values are widened to `u64`, the
+    /// usual type for metrics counters.
+    ///
+    /// ```rust
+    /// # use kioku::Arena;
+    /// let arena = Arena::new();
+    /// arena.alloc(1u32);
+    /// for (name, value) in arena.stats().counters().iter() {
+    ///     println!("kioku_{} {}", name, value);
+    /// }
+    /// ```
+    pub fn counters(&self) -> [(&'static str, u64); 9] {
+        [
+            ("occupied_bytes", self.occupied as u64),
+            ("allocated_bytes", self.allocated as u64),
+            ("blocks", self.block_count as u64),
+            ("oversize_blocks", self.oversize_block_count as u64),
+            ("oversize_allocated_bytes", self.oversize_allocated as u64),
+            ("allocations", self.alloc_count),
+            ("allocations_total", self.total_alloc_count),
+            ("peak_allocated_bytes", self.peak_allocated as u64),
+            ("peak_blocks", self.peak_block_count as u64),
+        ]
+    }
+}
+
+/// Arena types whose statistics can be collected, e.g. periodically by a
+/// metrics exporter.
+///
+/// ```rust
+/// # use kioku::{ArenaMetrics, SyncArena};
+/// use std::sync::Arc;
+///
+/// let arena = Arc::new(SyncArena::new());
+/// let monitored: Arc<dyn ArenaMetrics + Send + Sync> = arena.clone();
+///
+/// arena.alloc(1u32);
+/// assert_eq!(4, monitored.arena_stats().allocated);
+/// ```
+pub trait ArenaMetrics {
+    /// The arena's current statistics.
+    fn arena_stats(&self) -> ArenaStats;
+}
+
+impl<A: Allocator + Clone> ArenaMetrics for Arena<A> {
+    fn arena_stats(&self) -> ArenaStats {
+        self.stats()
+    }
+}
+
+impl ArenaMetrics for SyncArena {
+    fn arena_stats(&self) -> ArenaStats {
+        self.stats()
+    }
+}
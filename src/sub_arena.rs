@@ -0,0 +1,141 @@
+use std::{
+    alloc::Layout, cmp::Reverse, fmt, marker::PhantomData, mem, mem::MaybeUninit, ops, ptr::NonNull,
+};
+
+use allocator_api2::alloc::{Allocator, Global};
+
+use crate::{block::Block, Arena, ArenaAllocError, RetentionPolicy};
+
+/// An arena whose blocks are allocated from a parent arena.
+///
+/// Created by `Arena::sub_arena()`.  This derefs to `Arena`, so it has the
+/// full allocation API, and its allocations can't outlive either it or the
+/// parent.  When the sub-arena is cleared or dropped its memory is given
+/// back to the parent, which can then reuse it.
+///
+/// Like deallocation elsewhere in the crate, giving memory back only works
+/// for the parent's most recent allocations, so this is most effective when
+/// the parent isn't allocating from elsewhere while the sub-arena is in use.
+/// Otherwise the memory is reclaimed when the parent is cleared or dropped.
+///
+/// ```rust
+/// # use kioku::Arena;
+/// let parent = Arena::new();
+/// let before = parent.alloc(1u128) as *mut u128;
+/// {
+///     let child = parent.sub_arena();
+///     let text = child.copy_str("Hello!");
+///     assert_eq!("Hello!", text);
+/// }
+/// // The child's memory was given back.
+/// let after = parent.alloc(2u128) as *mut u128;
+/// assert_eq!(unsafe { before.add(1) }, after);
+/// ```
+pub struct SubArena<'p, A: Allocator + Clone = Global> {
+    arena: Arena<A>,
+    _phantom: PhantomData<&'p Arena<A>>,
+}
+
+/// A pointer to the parent of a sub-arena, for allocating its blocks.
+pub(crate) struct ParentRef<A: Allocator + Clone>(NonNull<Arena<A>>);
+
+// Only sub-arenas have a parent, and they can't be sent to other threads
+// since `Arena` isn't `Sync`.
+unsafe impl<A: Allocator + Clone> Send for ParentRef<A> {}
+
+impl<A: Allocator + Clone> ParentRef<A> {
+    pub fn try_alloc_block(
+        &self,
+        capacity: usize,
+        align: usize,
+    ) -> Result<Block<A>, ArenaAllocError> {
+        let layout = Layout::from_size_align(capacity, align)
+            .map_err(|_| ArenaAllocError::LayoutOverflow)?;
+
+        // Safe because the sub-arena borrows the parent.
+        let parent = unsafe { self.0.as_ref() };
+        let memory = parent.alloc_raw_impl(layout)?;
+        Ok(unsafe { Block::from_raw_parts(NonNull::new_unchecked(memory), capacity, align) })
+    }
+
+    /// Gives the unused end of `block` back to the parent, if possible.
+    pub fn give_back_unused(&self, block: &Block<A>) -> bool {
+        let parent = unsafe { self.0.as_ref() };
+        parent.try_resize_in_place(block.as_ptr(), block.capacity(), block.len())
+    }
+}
+
+impl<'p, A: Allocator + Clone> SubArena<'p, A> {
+    /// Creates a sub-arena with the same settings as `parent`, except for a
+    /// block size a quarter of the parent's.
+    pub(crate) fn new(parent: &'p Arena<A>) -> SubArena<'p, A> {
+        let mut arena = parent
+            .new_with_same_settings()
+            .with_block_size((parent.min_block_size / 4).max(1))
+            // Blocks are given back to the parent instead.
+            .with_retention_policy(RetentionPolicy::Nothing);
+        arena.parent = Some(ParentRef(NonNull::from(parent)));
+        // The blocks count towards the parent's memory limit instead.
+        arena.memory_limit = None;
+
+        SubArena {
+            arena,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Build a sub-arena with a specified block size in bytes.
+    ///
+    /// See `Arena::with_block_size()`.
+    pub fn with_block_size(mut self, block_size: usize) -> SubArena<'p, A> {
+        let placeholder = self.arena.new_with_same_settings();
+        let arena = mem::replace(&mut self.arena, placeholder);
+        self.arena = arena.with_block_size(block_size);
+        self
+    }
+
+    /// Frees all memory currently allocated by the sub-arena, giving it back
+    /// to the parent where possible.
+    pub fn clear(&mut self) {
+        let mut blocks: Vec<(*const MaybeUninit<u8>, usize)> = self
+            .arena
+            .blocks
+            .borrow()
+            .iter()
+            .map(|block| (block.as_ptr(), block.capacity()))
+            .collect();
+        self.arena.clear();
+
+        // Within each of the parent's blocks, our blocks are at increasing
+        // addresses, so giving them back from the highest address down lets
+        // the parent reclaim as many of them as possible.
+        blocks.sort_by_key(|&(ptr, _)| Reverse(ptr as usize));
+        if let Some(parent) = &self.arena.parent {
+            let parent = unsafe { parent.0.as_ref() };
+            for (ptr, capacity) in blocks {
+                parent.try_resize_in_place(ptr, capacity, 0);
+            }
+        }
+    }
+}
+
+impl<'p, A: Allocator + Clone> ops::Deref for SubArena<'p, A> {
+    type Target = Arena<A>;
+
+    #[inline]
+    fn deref(&self) -> &Arena<A> {
+        &self.arena
+    }
+}
+
+impl<'p, A: Allocator + Clone> Drop for SubArena<'p, A> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<'p, A: Allocator + Clone> fmt::Debug for SubArena<'p, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("SubArena").field(&self.arena).finish()
+    }
+}
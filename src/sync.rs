@@ -0,0 +1,157 @@
+use std::{
+    alloc::Layout,
+    mem::MaybeUninit,
+    sync::{Mutex, MutexGuard},
+};
+
+use crate::{Arena, ArenaStats};
+
+/// A thread-safe arena, which can be shared between threads.
+///
+/// This wraps an `Arena` in a mutex, so that multiple threads can allocate
+/// from it concurrently.  The references it returns are valid until the arena
+/// is cleared or dropped, just like with `Arena`.
+///
+/// Only a subset of `Arena`'s allocation methods are provided, but
+/// `get_mut()` gives access to the full `Arena` API when there are no
+/// outstanding allocations.
+///
+/// ```rust
+/// # use kioku::SyncArena;
+/// let arena = SyncArena::new();
+/// let (a, b) = std::thread::scope(|s| {
+///     let a = s.spawn(|| arena.copy_str("Hello"));
+///     let b = s.spawn(|| arena.alloc_array(7u32, 3));
+///     (a.join().unwrap(), b.join().unwrap())
+/// });
+/// assert_eq!("Hello", a);
+/// assert_eq!(&[7, 7, 7], b);
+/// ```
+#[derive(Debug, Default)]
+pub struct SyncArena {
+    arena: Mutex<Arena>,
+}
+
+impl SyncArena {
+    /// Create a new thread-safe arena.
+    pub fn new() -> SyncArena {
+        SyncArena::from_arena(Arena::new())
+    }
+
+    /// Create a new thread-safe arena from an existing arena, including its
+    /// settings and any memory it has already allocated.
+    pub fn from_arena(arena: Arena) -> SyncArena {
+        SyncArena {
+            arena: Mutex::new(arena),
+        }
+    }
+
+    /// Unwraps the underlying arena.
+    pub fn into_inner(self) -> Arena {
+        self.arena.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Mutable access to the underlying arena.
+    ///
+    /// Since this takes `&mut self`, there can't be any outstanding
+    /// allocations, and thus nothing else accessing the arena.
+    pub fn get_mut(&mut self) -> &mut Arena {
+        self.arena.get_mut().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Frees all memory currently allocated by the arena.
+    pub fn clear(&mut self) {
+        self.get_mut().clear();
+    }
+
+    /// Returns statistics about the arena's current memory usage.
+    ///
+    /// See `Arena::stats()`.
+    pub fn stats(&self) -> ArenaStats {
+        self.lock().stats()
+    }
+
+    /// Allocates a `T` initialized to `value`.
+    #[inline]
+    pub fn alloc<T: Copy>(&self, value: T) -> &mut T {
+        self.with(|arena| arena.alloc(value))
+    }
+
+    /// Allocates a `[T]` with all elements initialized to `value`.
+    #[inline]
+    pub fn alloc_array<T: Copy>(&self, value: T, len: usize) -> &mut [T] {
+        self.with(|arena| arena.alloc_array(value, len))
+    }
+
+    /// Allocates a `[T]` with each element initialized to the return value of
+    /// `f` called with that element's index, in parallel.
+    ///
+    /// See `Arena::alloc_array_par_with()`.  The lock is only held while allocating.
+    #[cfg(feature = "rayon")]
+    pub fn alloc_array_par_with<T, F>(&self, len: usize, f: F) -> &mut [T]
+    where
+        T: Copy + Send,
+        F: Fn(usize) -> T + Sync + Send,
+    {
+        crate::par::fill(self.alloc_array_uninit(len), f)
+    }
+
+    /// Allocates a `[T]` initialized to the contents of `slice`.
+    #[inline]
+    pub fn copy_slice<T: Copy>(&self, slice: &[T]) -> &mut [T] {
+        self.with(|arena| arena.copy_slice(slice))
+    }
+
+    /// Allocates a `str` initialized to the contents of `text`.
+    #[inline]
+    pub fn copy_str(&self, text: &str) -> &mut str {
+        self.with(|arena| arena.copy_str(text))
+    }
+
+    /// Allocates a `T` initialized to `value`, which is dropped when the arena
+    /// is cleared or dropped.
+    ///
+    /// See `Arena::alloc_droppable()`.
+    #[inline]
+    pub fn alloc_droppable<T: Send + 'static>(&self, value: T) -> &mut T {
+        self.with(|arena| arena.alloc_droppable(value))
+    }
+
+    /// Allocates an uninitialized `T`.
+    #[inline]
+    pub fn alloc_uninit<T>(&self) -> &mut MaybeUninit<T> {
+        self.with(|arena| arena.alloc_uninit())
+    }
+
+    /// Allocates a uninitialized `[T]`.
+    #[inline]
+    pub fn alloc_array_uninit<T>(&self, len: usize) -> &mut [MaybeUninit<T>] {
+        self.with(|arena| arena.alloc_array_uninit(len))
+    }
+
+    /// Allocates uninitialized memory with the given layout.
+    ///
+    /// See `Arena::alloc_raw()` for the caveats.
+    #[inline]
+    pub fn alloc_raw(&self, layout: Layout) -> *mut MaybeUninit<u8> {
+        self.lock().alloc_raw(layout)
+    }
+
+    #[inline(always)]
+    fn lock(&self) -> MutexGuard<'_, Arena> {
+        // A panic while locked can't leave the arena in an inconsistent
+        // state, so poisoning is ignored.
+        self.arena.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Runs the allocation function `f` with the lock held, and extends the
+    /// lifetime of its result to that of `self`.
+    #[inline(always)]
+    fn with<R: ?Sized, F: FnOnce(&Arena) -> &mut R>(&self, f: F) -> &mut R {
+        let ptr: *mut R = f(&self.lock());
+
+        // Safe because arena memory never moves, and is only freed when
+        // the arena is cleared or dropped, which requires `&mut self`.
+        unsafe { &mut *ptr }
+    }
+}
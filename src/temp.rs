@@ -0,0 +1,71 @@
+use std::{fmt, ops};
+
+use allocator_api2::alloc::{Allocator, Global};
+
+use crate::{Arena, Checkpoint};
+
+/// A temporary region of an arena, which is rolled back when dropped.
+///
+/// Created by `Arena::temp()`.  This derefs to the arena, so all of the usual
+/// allocation methods are available, but the allocations borrow from the
+/// guard and can't outlive it.  While the guard exists, the arena itself
+/// can't be used directly.
+///
+/// ```rust
+/// # use kioku::Arena;
+/// let mut arena = Arena::new();
+/// let mut temp = arena.temp();
+/// let a = temp.alloc(1u32) as *mut u32;
+/// {
+///     let nested = temp.temp();
+///     nested.alloc_array(0u8, 100);
+/// }
+/// let b = temp.alloc(2u32) as *mut u32;
+/// assert_eq!(unsafe { a.add(1) }, b);
+/// ```
+pub struct TempScope<'a, A: Allocator + Clone = Global> {
+    arena: &'a Arena<A>,
+    checkpoint: Checkpoint,
+}
+
+impl<'a, A: Allocator + Clone> TempScope<'a, A> {
+    pub(crate) fn new(arena: &'a mut Arena<A>) -> TempScope<'a, A> {
+        let checkpoint = arena.checkpoint();
+        TempScope { arena, checkpoint }
+    }
+
+    /// Starts a nested temporary region, which is rolled back when the
+    /// returned guard is dropped.
+    ///
+    /// Like `Arena::temp()`, this mutably borrows `self`, so any references
+    /// into the current region must be dropped first.
+    pub fn temp(&mut self) -> TempScope<'_, A> {
+        TempScope {
+            arena: self.arena,
+            checkpoint: self.arena.checkpoint(),
+        }
+    }
+}
+
+impl<'a, A: Allocator + Clone> ops::Deref for TempScope<'a, A> {
+    type Target = Arena<A>;
+
+    #[inline]
+    fn deref(&self) -> &Arena<A> {
+        self.arena
+    }
+}
+
+impl<'a, A: Allocator + Clone> Drop for TempScope<'a, A> {
+    fn drop(&mut self) {
+        // Safe because all allocations made through the guard borrow from
+        // it, and thus are no longer in use.
+        unsafe { self.arena.rewind(&self.checkpoint) }
+    }
+}
+
+impl<'a, A: Allocator + Clone> fmt::Debug for TempScope<'a, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("TempScope").field(self.arena).finish()
+    }
+}
@@ -0,0 +1,168 @@
+use std::{cell::RefCell, marker::PhantomData, mem::size_of, ptr, slice};
+
+use crate::Arena;
+
+/// An arena that stores values of a single type.
+///
+/// Unlike `Arena`, this runs the destructors of everything allocated in it
+/// when it's dropped, and it can iterate over everything allocated so far, in
+/// allocation order.
+///
+/// Values can refer to other values in the same arena, which makes it handy
+/// for building graphs:
+///
+/// ```rust
+/// # use kioku::TypedArena;
+/// use std::cell::Cell;
+///
+/// struct Node<'a> {
+///     name: String,
+///     next: Cell<Option<&'a Node<'a>>>,
+/// }
+///
+/// let arena = TypedArena::new();
+/// let a = arena.alloc(Node { name: "a".into(), next: Cell::new(None) });
+/// let b = arena.alloc(Node { name: "b".into(), next: Cell::new(Some(a)) });
+/// a.next.set(Some(b));
+/// assert_eq!("a", b.next.get().unwrap().name);
+/// ```
+///
+/// Iterating requires that there are no outstanding references into the
+/// arena, so it can't be done with graphs like the one above:
+///
+/// ```rust
+/// # use kioku::TypedArena;
+/// let mut arena = TypedArena::new();
+/// arena.alloc(String::from("a"));
+/// arena.alloc(String::from("b"));
+///
+/// let names: Vec<_> = arena.iter().cloned().collect();
+/// assert_eq!(vec!["a", "b"], names);
+/// ```
+pub struct TypedArena<T> {
+    // Must come before `arena`, so that the values are dropped before their
+    // memory is freed.
+    chunks: RefCell<ChunkList>,
+    arena: Arena,
+
+    // Tells the drop checker that we drop `T`s.  `ChunkList` does the actual
+    // dropping, but doesn't know the type.
+    _phantom: PhantomData<T>,
+}
+
+// The chunks only contain `T`s, and are owned exclusively.
+unsafe impl<T: Send> Send for TypedArena<T> {}
+
+impl<T> Default for TypedArena<T> {
+    fn default() -> TypedArena<T> {
+        TypedArena::new()
+    }
+}
+
+impl<T> TypedArena<T> {
+    /// Create a new typed arena.
+    pub fn new() -> TypedArena<T> {
+        assert!(
+            size_of::<T>() > 0,
+            "`TypedArena` does not support zero-sized types."
+        );
+
+        TypedArena {
+            chunks: RefCell::new(ChunkList {
+                chunks: Vec::new(),
+                drop_fn: drop_chunk::<T>,
+            }),
+            arena: Arena::new(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Moves `value` into the arena.
+    pub fn alloc(&self, value: T) -> &mut T {
+        let mut chunks = self.chunks.borrow_mut();
+
+        let needs_chunk = match chunks.chunks.last() {
+            Some(chunk) => chunk.len == chunk.capacity,
+            None => true,
+        };
+        if needs_chunk {
+            let capacity = match chunks.chunks.last() {
+                Some(chunk) => chunk.capacity * 2,
+                None => (self.arena.min_block_size / size_of::<T>()).max(1),
+            };
+            let memory = self.arena.alloc_array_uninit::<T>(capacity);
+            chunks.chunks.push(Chunk {
+                ptr: memory.as_mut_ptr() as *mut u8,
+                len: 0,
+                capacity,
+            });
+        }
+
+        let chunk = chunks.chunks.last_mut().unwrap();
+        unsafe {
+            let ptr = (chunk.ptr as *mut T).add(chunk.len);
+            ptr.write(value);
+            chunk.len += 1;
+            &mut *ptr
+        }
+    }
+
+    /// The number of values allocated so far.
+    pub fn len(&self) -> usize {
+        self.chunks.borrow().chunks.iter().map(|c| c.len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over all of the values allocated so far, in allocation order.
+    ///
+    /// This takes `&mut self` because `alloc()` hands out mutable references,
+    /// which must not be outstanding while iterating.
+    pub fn iter(&mut self) -> impl Iterator<Item = &T> {
+        self.chunks
+            .get_mut()
+            .chunks
+            .iter()
+            .flat_map(|chunk| unsafe {
+                slice::from_raw_parts(chunk.ptr as *const T, chunk.len).iter()
+            })
+    }
+
+    /// Mutably iterates over all of the values allocated so far, in
+    /// allocation order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.chunks
+            .get_mut()
+            .chunks
+            .iter()
+            .flat_map(|chunk| unsafe {
+                slice::from_raw_parts_mut(chunk.ptr as *mut T, chunk.len).iter_mut()
+            })
+    }
+}
+
+/// The type-erased storage of a `TypedArena`.
+struct ChunkList {
+    chunks: Vec<Chunk>,
+    drop_fn: unsafe fn(*mut u8, usize),
+}
+
+struct Chunk {
+    ptr: *mut u8,
+    len: usize,
+    capacity: usize,
+}
+
+unsafe fn drop_chunk<T>(ptr: *mut u8, len: usize) {
+    ptr::drop_in_place(ptr::slice_from_raw_parts_mut(ptr as *mut T, len));
+}
+
+impl Drop for ChunkList {
+    fn drop(&mut self) {
+        for chunk in self.chunks.drain(..) {
+            unsafe { (self.drop_fn)(chunk.ptr, chunk.len) };
+        }
+    }
+}
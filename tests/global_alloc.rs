@@ -0,0 +1,88 @@
+// These live in their own test binary, since they need a custom global
+// allocator.
+
+use kioku::{global::ScopedAlloc, Arena, StackArena};
+
+#[global_allocator]
+static ALLOC: ScopedAlloc = ScopedAlloc::new();
+
+#[test]
+fn with_arena_01() {
+    let arena = Arena::new();
+    let outside = vec![1u32, 2, 3];
+
+    unsafe {
+        kioku::global::with_arena(&arena, || {
+            // Check that heap allocations are bump allocated in the arena.
+            let a = Box::new(7u64);
+            let b = arena.alloc(8u64);
+            assert_eq!((&*a as *const u64).wrapping_add(1), b as *const u64);
+
+            let mut v: Vec<u32> = (0..1000).collect();
+            v.extend_from_slice(&outside);
+            assert_eq!("[0, 1, 2]", format!("{:?}", &v[..3]));
+            assert_eq!(1003, v.len());
+
+            // Direct use of the arena still works, including when it needs
+            // new blocks.
+            let big = arena.alloc_array(0u8, 1 << 16);
+            assert_eq!(1 << 16, big.len());
+        })
+    };
+
+    assert_eq!(&[1, 2, 3], &outside[..]);
+}
+
+#[test]
+fn with_arena_02() {
+    let arena_1 = Arena::new();
+    let arena_2 = Arena::new();
+
+    unsafe {
+        kioku::global::with_arena(&arena_1, || {
+            let mut outer = vec![1u8; 10];
+            kioku::global::with_arena(&arena_2, || {
+                // Grows the outer allocation from the inner scope.
+                outer.extend_from_slice(&[2; 100]);
+                let inner = Box::new([3u8; 10]);
+                assert_eq!(10, inner.len());
+            });
+            assert_eq!(110, outer.len());
+            drop(outer);
+        });
+    }
+
+    // Clearing the arena inside a scope doesn't get confused.
+    let mut arena_3 = Arena::new();
+    unsafe {
+        kioku::global::with_arena(&arena_1, || {
+            arena_3.alloc(1u32);
+            arena_3.clear();
+        });
+    }
+
+    // Heap allocations work normally again afterwards.
+    let v = Box::new([4u8; 10]);
+    assert_eq!(10, v.len());
+}
+
+#[test]
+fn stack_arena_no_heap_01() {
+    let probe = Arena::new();
+    let mut storage = StackArena::<256>::new();
+
+    unsafe {
+        kioku::global::with_arena(&probe, || {
+            // Any heap allocation would end up in the probe arena.
+            let mut arena = storage.arena();
+            let a = arena.alloc_array(1u32, 10);
+            assert_eq!(10, a.iter().sum::<u32>());
+            assert!(arena.try_alloc_array(0u8, 1000).is_err());
+            arena.clear();
+            assert_eq!(256, arena.remaining());
+            arena.copy_str("Hello");
+        })
+    };
+
+    assert_eq!(0, probe.stats().alloc_count);
+}
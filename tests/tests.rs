@@ -1,4 +1,7 @@
-use kioku::Arena;
+use kioku::{
+    abox_unsize, ABox, ARc, AllocError, Arena, ArenaHooks, ArenaOffsetPtr, ArenaStats, ArenaWriter,
+    ConcurrentArena, ShardedArena, StackBox, SyncArena, TypedArena,
+};
 
 #[test]
 fn alloc() {
@@ -7,6 +10,26 @@ fn alloc() {
     assert_eq!('A', *a);
 }
 
+#[test]
+fn alloc_with_01() {
+    let arena = Arena::new();
+    let a = arena.alloc_with(|| [42u64; 512]);
+    assert_eq!(&[42u64; 512], a);
+}
+
+#[test]
+fn alloc_default_01() {
+    #[derive(Copy, Clone, Debug, Default, PartialEq)]
+    struct Config {
+        a: u32,
+        b: Option<char>,
+    }
+
+    let arena = Arena::new();
+    let a = arena.alloc_default::<Config>();
+    assert_eq!(Config::default(), *a);
+}
+
 #[test]
 fn alloc_array_01() {
     let arena = Arena::new();
@@ -16,6 +39,64 @@ fn alloc_array_01() {
     assert_eq!(&['B'; 0][..], b);
 }
 
+#[test]
+fn alloc_fixed_array_01() {
+    let arena = Arena::new();
+    let a: &mut [char; 3] = arena.alloc_fixed_array('A');
+    let b = arena.alloc_fixed_array::<char, 0>('B');
+    assert_eq!(&['A', 'A', 'A'], a);
+    assert_eq!(&['B'; 0], b);
+}
+
+#[test]
+fn alloc_array_with_01() {
+    let arena = Arena::new();
+    let a = arena.alloc_array_with(4, |i| i * 2);
+    let b = arena.alloc_array_with(0, |_| 'A');
+    assert_eq!(&[0, 2, 4, 6], a);
+    assert_eq!(&['A'; 0][..], b);
+}
+
+#[test]
+fn alloc_array_default_01() {
+    let arena = Arena::new();
+    let a = arena.alloc_array_default::<Option<u32>>(3);
+    let b = arena.alloc_array_default::<u32>(0);
+    assert_eq!(&[None, None, None], a);
+    assert_eq!(&[0u32; 0][..], b);
+}
+
+#[test]
+fn alloc_array_split_01() {
+    let arena = Arena::new();
+    let [a, b, c, d] = arena.alloc_array_split('A', [2, 0, 3, 1]);
+    assert_eq!(&['A', 'A'], a);
+    assert_eq!(&['A'; 0][..], b);
+    assert_eq!(&['A', 'A', 'A'], c);
+    assert_eq!(&['A'], d);
+
+    // Make sure they're adjacent.
+    assert_eq!(a.as_ptr_range().end, c.as_ptr());
+    assert_eq!(c.as_ptr_range().end, d.as_ptr());
+}
+
+#[test]
+fn alloc_header_array_01() {
+    let arena = Arena::new();
+    let (a_head, a_tail) = arena.alloc_header_array(7u8, 1.5f64, 3);
+    let (b_head, b_tail) = arena.alloc_header_array('B', 0u8, 0);
+    assert_eq!(7, *a_head);
+    assert_eq!(&[1.5, 1.5, 1.5], a_tail);
+    assert_eq!('B', *b_head);
+    assert_eq!(&[0u8; 0][..], b_tail);
+
+    // Contiguous, with correct padding.
+    assert_eq!(
+        a_head as *const _ as usize + std::mem::align_of::<f64>(),
+        a_tail.as_ptr() as usize
+    );
+}
+
 #[test]
 fn copy_slice_01() {
     let arena = Arena::new();
@@ -25,6 +106,110 @@ fn copy_slice_01() {
     assert_eq!(&['D'; 0][..], b);
 }
 
+#[test]
+fn copy_array_01() {
+    let arena = Arena::new();
+    let a: &mut [char; 3] = arena.copy_array(&['A', 'B', 'C']);
+    let b = arena.copy_array::<char, 0>(&[]);
+    assert_eq!(&['A', 'B', 'C'], a);
+    assert_eq!(&['D'; 0], b);
+}
+
+#[test]
+fn concat_slices_01() {
+    let arena = Arena::new();
+    let a = arena.concat_slices(&[&['A', 'B'][..], &[], &['C']]);
+    let b = arena.concat_slices::<char>(&[]);
+    assert_eq!(&['A', 'B', 'C'], a);
+    assert_eq!(&['D'; 0][..], b);
+}
+
+#[test]
+#[should_panic(expected = "Array length overflow")]
+fn concat_slices_02() {
+    // Slices of zero-sized types can be as long as we like.
+    let huge: &[()] =
+        unsafe { std::slice::from_raw_parts(std::ptr::NonNull::dangling().as_ptr(), usize::MAX) };
+    Arena::new().concat_slices(&[huge, &[()]]);
+}
+
+#[test]
+fn alloc_from_iter_01() {
+    let arena = Arena::new();
+    let a = arena.alloc_from_iter((1..4).map(|n| n * 10));
+    let b = arena.alloc_from_iter(Vec::<char>::new());
+    assert_eq!(&[10, 20, 30], a);
+    assert_eq!(&['A'; 0][..], b);
+}
+
+#[test]
+fn alloc_from_iter_02() {
+    // Iterators without an exact size.
+    let arena = Arena::new().with_block_size(64);
+    let a = arena.alloc_from_iter((0..100).filter(|n| n % 3 == 0));
+    let b = arena.alloc_from_iter("".chars());
+    assert_eq!(34, a.len());
+    assert_eq!(99, a[33]);
+    assert_eq!(&['A'; 0][..], b);
+}
+
+#[test]
+fn alloc_from_iter_03() {
+    // Iterators with a wrong exact size hint.
+    struct Lying(std::ops::Range<u32>, usize);
+    impl Iterator for Lying {
+        type Item = u32;
+        fn next(&mut self) -> Option<u32> {
+            self.0.next()
+        }
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (self.1, Some(self.1))
+        }
+    }
+
+    let arena = Arena::new();
+    let a = arena.alloc_from_iter(Lying(0..10, 4));
+    let b = arena.alloc_from_iter(Lying(0..3, 8));
+    assert_eq!(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9], a);
+    assert_eq!(&[0, 1, 2], b);
+}
+
+#[test]
+fn alloc_vec_01() {
+    let arena = Arena::new();
+    let a = arena.alloc_vec(vec![String::from("a"), String::from("b")]);
+    let b = arena.alloc_vec(Vec::<u32>::new());
+    a[1].push('c');
+    assert_eq!(&["a", "bc"], a);
+    assert_eq!(&[0u32; 0], b);
+}
+
+#[test]
+fn alloc_closure_01() {
+    let arena = Arena::new();
+    let mut total = 0;
+    {
+        let a: &dyn Fn(u32) -> u32 = arena.alloc_closure(|x| x + 1);
+        let b: &mut dyn FnMut(u32) = arena.alloc_closure(|x| total += x);
+        b(a(1));
+        b(a(2));
+    }
+    assert_eq!(5, total);
+}
+
+#[test]
+fn alloc_iter_chunks_01() {
+    let arena = Arena::new().with_block_size(64);
+    let a = arena.alloc_iter_chunks(0..1000u32);
+    let b = arena.alloc_iter_chunks(0..0u32);
+
+    assert!(a.len() > 1);
+    assert!(a.iter().all(|chunk| !chunk.is_empty() && chunk.len() <= 16));
+    let items: Vec<u32> = a.iter().flat_map(|chunk| chunk.iter().copied()).collect();
+    assert_eq!((0..1000).collect::<Vec<_>>(), items);
+    assert!(b.is_empty());
+}
+
 #[test]
 fn copy_str_01() {
     let arena = Arena::new();
@@ -34,6 +219,269 @@ fn copy_str_01() {
     assert_eq!("", b);
 }
 
+#[test]
+fn collect_str_01() {
+    let arena = Arena::new().with_block_size(64);
+    let a = arena.collect_str("Hello there! こんにちは！".chars().rev());
+    let b = arena.collect_str((0..200).map(|_| '学'));
+    let c = arena.collect_str("".chars());
+    assert_eq!("！はちにんこ !ereht olleH", a);
+    assert_eq!("学".repeat(200), &*b);
+    assert_eq!("", c);
+}
+
+#[test]
+fn copy_str_lossy_01() {
+    let arena = Arena::new();
+    let a = arena.copy_str_lossy("Hello there! こんにちは！".as_bytes());
+    let b = arena.copy_str_lossy(b"A\xffB\xe3\x81C\xe3");
+    let c = arena.copy_str_lossy(b"");
+    assert_eq!("Hello there! こんにちは！", a);
+    assert_eq!(String::from_utf8_lossy(b"A\xffB\xe3\x81C\xe3"), &*b);
+    assert_eq!("", c);
+}
+
+#[test]
+fn copy_str_from_utf16_01() {
+    let text = "Hello there! こんにちは！🐢";
+    let utf16: Vec<u16> = text.encode_utf16().collect();
+
+    let arena = Arena::new();
+    let a = arena.copy_str_from_utf16(&utf16).unwrap();
+    let b = arena.copy_str_from_utf16(&[]).unwrap();
+    let c = arena.copy_str_from_utf16(&[0x48, 0xD800, 0x49]);
+    assert_eq!(text, a);
+    assert_eq!("", b);
+    assert!(c.is_err());
+}
+
+#[test]
+fn copy_str_from_utf16_lossy_01() {
+    let arena = Arena::new();
+    let a = arena.copy_str_from_utf16_lossy(&[0x48, 0xD800, 0x49]);
+    let b = arena.copy_str_from_utf16_lossy(&[]);
+    assert_eq!("H\u{FFFD}I", a);
+    assert_eq!("", b);
+}
+
+#[test]
+fn copy_cstr_01() {
+    use std::ffi::CStr;
+
+    let arena = Arena::new();
+    let a = arena.copy_cstr(CStr::from_bytes_with_nul(b"Hello there!\0").unwrap());
+    let b = arena.copy_cstr(CStr::from_bytes_with_nul(b"\0").unwrap());
+    assert_eq!(b"Hello there!\0", a.to_bytes_with_nul());
+    assert_eq!(b"\0", b.to_bytes_with_nul());
+}
+
+#[cfg(feature = "bstr")]
+#[test]
+fn copy_bstr_01() {
+    use bstr::{BStr, ByteSlice};
+
+    let arena = Arena::new();
+    let a = arena.copy_bstr(BStr::new(b"Hello\xffthere!"));
+    let b = arena.copy_bstr(BStr::new(b""));
+    assert_eq!(BStr::new(b"Hello\xffthere!"), a);
+    assert_eq!(Some(5), a.find_byte(b'\xff'));
+    assert!(b.is_empty());
+}
+
+#[test]
+fn concat_strs_01() {
+    let arena = Arena::new();
+    let a = arena.concat_strs(&["Hello", "", " there! ", "こんにちは！"]);
+    let b = arena.concat_strs(&[]);
+    assert_eq!("Hello there! こんにちは！", a);
+    assert_eq!("", b);
+}
+
+#[test]
+fn join_strs_01() {
+    let arena = Arena::new();
+    let a = arena.join_strs(&["a", "b", "", "こんにちは"], ", ");
+    let b = arena.join_strs(&["a"], ", ");
+    let c = arena.join_strs(&[], ", ");
+    assert_eq!("a, b, , こんにちは", a);
+    assert_eq!("a", b);
+    assert_eq!("", c);
+}
+
+#[test]
+fn alloc_fmt_01() {
+    let arena = Arena::new();
+    let a = arena.alloc_fmt(format_args!("x = {}, y = {:?}", 42, 'A'));
+    let b = arena.alloc_fmt(format_args!("こんにちは！"));
+    let c = arena.alloc_fmt(format_args!("{}", ""));
+    assert_eq!("x = 42, y = 'A'", a);
+    assert_eq!("こんにちは！", b);
+    assert_eq!("", c);
+}
+
+#[test]
+fn alloc_clone_01() {
+    #[derive(Clone, Debug, PartialEq)]
+    struct Wrapper(u32);
+
+    let arena = Arena::new();
+    let a = arena.alloc_clone(&Wrapper(42));
+    assert_eq!(Wrapper(42), *a);
+}
+
+#[test]
+fn copy_slice_clone_01() {
+    #[derive(Clone, Debug, PartialEq)]
+    struct Wrapper(u32);
+
+    let arena = Arena::new();
+    let a = arena.copy_slice_clone(&[Wrapper(1), Wrapper(2), Wrapper(3)]);
+    let b = arena.copy_slice_clone::<Wrapper>(&[]);
+    assert_eq!(&[Wrapper(1), Wrapper(2), Wrapper(3)], a);
+    assert_eq!(&[Wrapper(4); 0][..], b);
+}
+
+struct DropRecorder(u32, std::sync::Arc<std::sync::Mutex<Vec<u32>>>);
+
+impl Drop for DropRecorder {
+    fn drop(&mut self) {
+        self.1.lock().unwrap().push(self.0);
+    }
+}
+
+#[test]
+fn alloc_droppable_01() {
+    let log = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    {
+        let arena = Arena::new();
+        let a = arena.alloc_droppable(DropRecorder(1, log.clone()));
+        let b = arena.alloc_droppable(DropRecorder(2, log.clone()));
+        let c = arena.alloc_droppable(42u32);
+        assert_eq!(1, a.0);
+        assert_eq!(2, b.0);
+        assert_eq!(42, *c);
+        assert!(log.lock().unwrap().is_empty());
+    }
+    assert_eq!(vec![2, 1], *log.lock().unwrap());
+}
+
+#[test]
+fn alloc_droppable_02() {
+    let log = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut arena = Arena::new();
+    arena.alloc_droppable(DropRecorder(1, log.clone()));
+    arena.clear();
+    assert_eq!(vec![1], *log.lock().unwrap());
+
+    arena.alloc_droppable(DropRecorder(2, log.clone()));
+    drop(arena);
+    assert_eq!(vec![1, 2], *log.lock().unwrap());
+}
+
+#[test]
+fn alloc_pinned_01() {
+    struct Pinned(u32, std::marker::PhantomPinned);
+
+    let arena = Arena::new();
+    let a = arena.alloc_pinned(Pinned(42, std::marker::PhantomPinned));
+    let mut b = arena.alloc_pinned_uninit::<Pinned>();
+    unsafe {
+        b.as_mut()
+            .get_unchecked_mut()
+            .write(Pinned(7, std::marker::PhantomPinned));
+    }
+    assert_eq!(42, a.0);
+    assert_eq!(7, unsafe { b.assume_init_ref().0 });
+}
+
+#[test]
+fn abox_01() {
+    let log = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let arena = Arena::new();
+    let a = ABox::new_in(DropRecorder(1, log.clone()), &arena);
+    let b = ABox::new_in(DropRecorder(2, log.clone()), &arena);
+    assert_eq!(2, b.0);
+    drop(b);
+    assert_eq!(vec![2], *log.lock().unwrap());
+    drop(a);
+    assert_eq!(vec![2, 1], *log.lock().unwrap());
+}
+
+#[test]
+fn abox_02() {
+    let arena = Arena::new();
+    let mut a = ABox::new_in(vec![1, 2, 3], &arena);
+    a.push(4);
+    assert_eq!(&[1, 2, 3, 4], &a[..]);
+    assert_eq!(vec![1, 2, 3, 4], ABox::into_inner(a));
+
+    let b = ABox::leak(ABox::new_in(String::from("Hello"), &arena));
+    assert_eq!("Hello", b);
+}
+
+#[test]
+fn abox_unsize_01() {
+    let log = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let arena = Arena::new();
+    let a: ABox<dyn Send> = abox_unsize!(ABox::new_in(DropRecorder(1, log.clone()), &arena));
+    let b: ABox<[u32]> = abox_unsize!(ABox::new_in([1, 2, 3], &arena));
+    assert_eq!(&[1, 2, 3], &*b);
+    drop(a);
+    assert_eq!(vec![1], *log.lock().unwrap());
+}
+
+#[test]
+fn arc_01() {
+    let log = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let arena = Arena::new();
+    let a = ARc::new_in(DropRecorder(1, log.clone()), &arena);
+    let b = a.clone();
+    assert_eq!(2, ARc::strong_count(&b));
+    drop(a);
+    assert!(log.lock().unwrap().is_empty());
+    assert_eq!(1, b.0);
+    drop(b);
+    assert_eq!(vec![1], *log.lock().unwrap());
+}
+
+#[test]
+fn arc_02() {
+    let arena = Arena::new();
+    let mut a = ARc::new_in(vec![1, 2], &arena);
+    ARc::get_mut(&mut a).unwrap().push(3);
+    let b = a.clone();
+    assert!(ARc::get_mut(&mut a).is_none());
+    let a = ARc::try_unwrap(a).unwrap_err();
+    drop(b);
+    assert_eq!(Ok(vec![1, 2, 3]), ARc::try_unwrap(a));
+}
+
+#[test]
+fn typed_arena_01() {
+    let log = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    {
+        let mut arena = TypedArena::new();
+        for i in 0..1000 {
+            assert_eq!(i, arena.alloc(DropRecorder(i, log.clone())).0);
+        }
+        assert_eq!(1000, arena.len());
+        assert!(arena.iter().map(|r| r.0).eq(0..1000));
+        for r in arena.iter_mut() {
+            r.0 += 1;
+        }
+        assert!(arena.iter().map(|r| r.0).eq(1..1001));
+        assert!(log.lock().unwrap().is_empty());
+    }
+    assert_eq!((1..1001).collect::<Vec<_>>(), *log.lock().unwrap());
+}
+
+#[test]
+fn typed_arena_02() {
+    let mut arena = TypedArena::<u64>::new();
+    assert!(arena.is_empty());
+    assert_eq!(0, arena.iter().count());
+}
+
 #[test]
 fn alloc_align_01() {
     let arena = Arena::new();
@@ -71,6 +519,52 @@ fn copy_slice_align_01() {
     assert_eq!(0, &b[0] as *const _ as usize % 64);
 }
 
+#[test]
+fn alloc_cacheline_01() {
+    let arena = Arena::new();
+    let a = arena.alloc_cacheline('A');
+    let b = arena.alloc_array_cacheline('B', 3);
+    assert_eq!('A', *a);
+    assert_eq!(&['B', 'B', 'B'], b);
+    assert_eq!(0, a as *const _ as usize % kioku::CACHE_LINE);
+    assert_eq!(0, b.as_ptr() as usize % kioku::CACHE_LINE);
+}
+
+#[test]
+fn alloc_array_strided_01() {
+    let arena = Arena::new();
+    let mut a = arena.alloc_array_strided('A', 3, 64);
+    let b = arena.alloc_array_strided('B', 0, 64);
+    a[1] = 'C';
+    assert_eq!(64, a.stride());
+    assert_eq!(3, a.len());
+    assert_eq!(vec!['A', 'C', 'A'], a.iter().copied().collect::<Vec<_>>());
+    for i in 0..3 {
+        assert_eq!(0, a.get(i).unwrap() as *const _ as usize % 64);
+    }
+    assert_eq!(None, a.get(3));
+    assert!(b.is_empty());
+}
+
+#[test]
+fn alloc_array_2d_01() {
+    let arena = Arena::new();
+    let mut a = arena.alloc_array_2d(0u8, 3, 4, 16);
+    let b = arena.alloc_array_2d(0u8, 0, 0, 16);
+    a[(2, 1)] = 5;
+    a.row_mut(3)[0] = 7;
+    assert_eq!(16, a.pitch());
+    assert_eq!((3, 4), (a.width(), a.height()));
+    assert_eq!(&[0, 0, 5], a.row(1));
+    assert_eq!(7, a[(0, 3)]);
+    for y in 0..4 {
+        assert_eq!(0, a.row(y).as_ptr() as usize % 16);
+    }
+    assert_eq!(None, a.get(3, 0));
+    assert_eq!(None, a.get(0, 4));
+    assert_eq!(0, b.rows().count());
+}
+
 #[test]
 fn alloc_uninit_01() {
     let arena = Arena::new();
@@ -87,6 +581,20 @@ fn alloc_array_uninit_01() {
     assert_eq!(0, b.len());
 }
 
+#[test]
+fn uninit_non_copy_01() {
+    let arena = Arena::new();
+    let a = arena.alloc_uninit::<String>();
+    let b = arena.alloc_array_uninit::<Vec<u8>>(3);
+    let c = arena.alloc_align_uninit::<String>(64);
+    let d = arena.alloc_array_align_uninit::<Vec<u8>>(3, 64);
+    a.write(String::from("Hello"));
+    assert_eq!("Hello", unsafe { a.assume_init_ref() });
+    assert_eq!(3, b.len());
+    assert_eq!(0, c as *const _ as usize % 64);
+    assert_eq!(3, d.len());
+}
+
 #[test]
 fn alloc_align_uninit_01() {
     let arena = Arena::new();
@@ -110,78 +618,1825 @@ fn alloc_array_align_uninit_01() {
 }
 
 #[test]
-fn lots_of_allocs_01() {
-    // To force multiple blocks.
-    let arena = Arena::new().with_block_size(64);
+fn copy_unaligned_01() {
+    let data: Vec<u8> = (0..17).collect();
 
-    for _ in 0..512 {
-        let a = arena.alloc('A');
-        assert_eq!('A', *a);
-    }
+    let arena = Arena::new();
+    let a = unsafe { arena.copy_unaligned::<u32>(&data[1..]) };
+    let b = unsafe { arena.copy_unaligned::<u32>(&[]) };
+    assert_eq!(
+        &[
+            u32::from_ne_bytes([1, 2, 3, 4]),
+            u32::from_ne_bytes([5, 6, 7, 8]),
+            u32::from_ne_bytes([9, 10, 11, 12]),
+            u32::from_ne_bytes([13, 14, 15, 16]),
+        ],
+        a
+    );
+    assert_eq!(&[0u32; 0][..], b);
 }
 
 #[test]
-fn big_alloc_01() {
-    // To make sure larger-than-block-size allocations succeed.
-    let arena = Arena::new().with_block_size(64);
-    let a = arena.alloc('A');
-    let b = arena.alloc('B');
-    let c = arena.alloc_array(['C'; 8], 32);
-    let d = arena.alloc('D');
-    let e = arena.alloc('E');
-
-    assert_eq!('A', *a);
-    assert_eq!('B', *b);
-    assert_eq!(&[['C'; 8]; 32], c);
-    assert_eq!('D', *d);
-    assert_eq!('E', *e);
+#[should_panic]
+fn copy_unaligned_02() {
+    let arena = Arena::new();
+    unsafe { arena.copy_unaligned::<u32>(&[1, 2, 3]) };
 }
 
-//-----------------------------------------------------------
-// Tests to make sure malformed alignments are rejected.
-
+#[cfg(feature = "bytemuck")]
 #[test]
-#[should_panic]
-fn alloc_align_malformed_01() {
-    Arena::new().alloc_align('A', 6);
+fn bytemuck_01() {
+    let data: Vec<u8> = (0..17).collect();
+
+    let arena = Arena::new();
+    let a = arena.alloc_zeroable::<[u32; 4]>();
+    let b = arena.alloc_array_zeroable::<f32>(3);
+    let c = arena.copy_pod_from_bytes::<u16>(&data[1..]);
+    let d = arena.copy_cast_slice::<u8, u32>(&data[1..]);
+    assert_eq!(&[0u32; 4], a);
+    assert_eq!(&[0.0f32; 3], b);
+    assert_eq!(u16::from_ne_bytes([1, 2]), c[0]);
+    assert_eq!(8, c.len());
+    assert_eq!(u32::from_ne_bytes([13, 14, 15, 16]), d[3]);
+    assert_eq!(4, d.len());
 }
 
+#[cfg(feature = "zerocopy")]
 #[test]
-#[should_panic]
-fn alloc_align_malformed_02() {
-    Arena::new().alloc_align('A', 0);
-}
+fn zerocopy_01() {
+    let data: Vec<u8> = (0..17).collect();
 
-//-----------------------------------------------------------
-// Tests to make sure zero-sized types are rejected.
+    let arena = Arena::new();
+    let a = arena.copy_from_bytes::<u32>(&data[1..5]).unwrap();
+    let (b, rest) = arena.copy_from_prefix::<u64>(&data[1..]).unwrap();
+    let c = arena.copy_slice_from_bytes::<u16>(&data[1..]).unwrap();
+    assert_eq!(u32::from_ne_bytes([1, 2, 3, 4]), *a);
+    assert_eq!(u64::from_ne_bytes([1, 2, 3, 4, 5, 6, 7, 8]), *b);
+    assert_eq!(&data[9..], rest);
+    assert_eq!(u16::from_ne_bytes([15, 16]), c[7]);
 
-#[test]
-#[should_panic]
-fn zero_sized_types_01() {
-    Arena::new().alloc(());
+    assert!(arena.copy_from_bytes::<u32>(&data[1..4]).is_none());
+    assert!(arena.copy_from_prefix::<u32>(&data[1..4]).is_none());
+    assert!(arena.copy_slice_from_bytes::<u16>(&data[1..4]).is_none());
 }
 
+#[cfg(feature = "allocator-api2")]
 #[test]
-#[should_panic]
-fn zero_sized_types_02() {
-    Arena::new().alloc_array((), 0);
+fn allocator_api2_01() {
+    let arena = Arena::new();
+    let mut a = allocator_api2::vec::Vec::new_in(&arena);
+    let b = allocator_api2::boxed::Box::new_in(String::from("Hello"), &arena);
+    for i in 0..100u32 {
+        a.push(i);
+    }
+    a.truncate(10);
+    a.shrink_to_fit();
+    assert_eq!(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9], &a[..]);
+    assert_eq!("Hello", &*b);
 }
 
+#[cfg(feature = "allocator-api2")]
 #[test]
-#[should_panic]
-fn zero_sized_types_03() {
-    Arena::new().copy_slice(&[()]);
+fn allocator_api2_02() {
+    // Growing past the memory limit is an error rather than a panic.
+    let arena = Arena::new().with_block_size(64).with_memory_limit(4096);
+    let mut a = allocator_api2::vec::Vec::<u8, _>::new_in(&arena);
+    a.push(1);
+    assert!(a.try_reserve(100_000).is_err());
+    assert_eq!(&[1], &a[..]);
+    assert!(a.try_reserve(100).is_ok());
 }
 
+#[cfg(feature = "hashbrown")]
 #[test]
-#[should_panic]
-fn zero_sized_types_04() {
-    Arena::new().alloc_align((), 4);
+fn hashbrown_01() {
+    use kioku::collections::{HashMap, HashSet};
+
+    let arena = Arena::new();
+    let mut map = HashMap::with_capacity_in(4, &arena);
+    let mut set = HashSet::new_in(&arena);
+    for i in 0..100u32 {
+        map.insert(i, i * 2);
+        set.insert(i % 10);
+    }
+    assert_eq!(100, map.len());
+    assert_eq!(Some(&84), map.get(&42));
+    assert_eq!(10, set.len());
 }
 
+#[cfg(feature = "rayon")]
 #[test]
-#[should_panic]
-fn zero_sized_types_05() {
+fn alloc_array_par_with_01() {
+    let arena = Arena::new();
+    let array = arena.alloc_array_par_with(10000, |i| i as u64 * 3);
+    assert_eq!(10000, array.len());
+    assert!(array.iter().enumerate().all(|(i, &n)| n == i as u64 * 3));
+
+    let arena = ConcurrentArena::new();
+    let array = arena.alloc_array_par_with(100, |i| i as u8);
+    assert_eq!(99, array[99]);
+}
+
+#[test]
+fn alloc_layout_01() {
+    use std::alloc::Layout;
+
+    let arena = Arena::new();
+    let a = arena.alloc_layout(Layout::from_size_align(24, 8).unwrap());
+    let b = arena.alloc_layout(Layout::from_size_align(3, 64).unwrap());
+    let c = arena.alloc_layout(Layout::from_size_align(0, 1).unwrap());
+    assert_eq!(24, a.len());
+    assert_eq!(3, b.len());
+    assert_eq!(0, c.len());
+    assert_eq!(0, a.as_ptr() as usize % 8);
+    assert_eq!(0, b.as_ptr() as usize % 64);
+}
+
+#[test]
+fn alloc_zeroed_01() {
+    let arena = Arena::new();
+    let a = arena.alloc_array(0xffu8, 64);
+    let b = unsafe { arena.alloc_zeroed::<[u32; 16]>() };
+    assert_eq!(&[0xffu8; 64][..], a);
+    assert_eq!(&[0u32; 16], b);
+}
+
+#[test]
+fn alloc_array_zeroed_01() {
+    let arena = Arena::new();
+    let a = arena.alloc_array(0xffu8, 64);
+    let b = unsafe { arena.alloc_array_zeroed::<u32>(4000) };
+    let c = unsafe { arena.alloc_array_zeroed::<u32>(0) };
+    assert_eq!(&[0xffu8; 64][..], a);
+    assert_eq!(4000, b.len());
+    assert!(b.iter().all(|n| *n == 0));
+    assert_eq!(&[0u32; 0][..], c);
+}
+
+#[test]
+fn extend_last_slice_01() {
+    let arena = Arena::new();
+    let mut a = arena.copy_slice(&['A', 'B']);
+    assert!(arena.extend_last_slice(&mut a, &['C', 'D']));
+    assert!(arena.extend_last_slice(&mut a, &[]));
+    let b = arena.alloc('E');
+    assert!(!arena.extend_last_slice(&mut a, &['F']));
+
+    assert_eq!(&['A', 'B', 'C', 'D'], a);
+    assert_eq!('E', *b);
+}
+
+#[test]
+fn extend_last_slice_02() {
+    // Not enough room in the block.
+    let arena = Arena::new().with_block_size(64);
+    let mut a = arena.alloc_array(0u8, 60);
+    assert!(!arena.extend_last_slice(&mut a, &[1; 8]));
+    assert_eq!(&[0u8; 60][..], a);
+}
+
+#[test]
+fn shrink_last_slice_01() {
+    let arena = Arena::new();
+    let mut a = arena.copy_slice(&['A', 'B', 'C', 'D']);
+    assert!(arena.shrink_last_slice(&mut a, 2));
+    let b = arena.alloc('E');
+    assert!(!arena.shrink_last_slice(&mut a, 1));
+
+    assert_eq!(&['A'], a);
+    assert_eq!('E', *b);
+    // The reclaimed space should have been reused.
+    assert_eq!(a.as_ptr() as usize + 8, b as *const _ as usize);
+}
+
+#[test]
+fn alloc_raw_nonnull_01() {
+    use std::alloc::Layout;
+
+    let arena = Arena::new();
+    let a = arena.alloc_raw_nonnull(Layout::from_size_align(24, 64).unwrap());
+    let b = arena.alloc_raw_nonnull(Layout::from_size_align(0, 1).unwrap());
+    assert_eq!(24, a.len());
+    assert_eq!(0, b.len());
+    assert_eq!(0, a.as_ptr() as *const u8 as usize % 64);
+}
+
+#[test]
+fn realloc_raw_01() {
+    use std::alloc::Layout;
+
+    let arena = Arena::new();
+    let layout_a = Layout::array::<u8>(4).unwrap();
+    let layout_b = Layout::array::<u8>(64).unwrap();
+    let layout_c = Layout::array::<u8>(128).unwrap();
+    let layout_d = Layout::array::<u8>(2).unwrap();
+    unsafe {
+        let a = arena.alloc_raw(layout_a);
+        (a as *mut u8).copy_from([1, 2, 3, 4].as_ptr(), 4);
+
+        // Grow in place.
+        let b = arena.realloc_raw(a, layout_a, layout_b);
+        assert_eq!(a, b);
+
+        // Grow by moving.
+        arena.alloc('Z');
+        let c = arena.realloc_raw(b, layout_b, layout_c);
+        assert_ne!(b, c);
+        assert_eq!(
+            &[1u8, 2, 3, 4],
+            std::slice::from_raw_parts(c as *const u8, 4)
+        );
+
+        // Shrink.
+        let d = arena.realloc_raw(c, layout_c, layout_d);
+        assert_eq!(c, d);
+        assert_eq!(&[1u8, 2], std::slice::from_raw_parts(d as *const u8, 2));
+    }
+}
+
+#[test]
+fn block_alignment_01() {
+    let arena = Arena::new().with_block_size(64).with_block_alignment(4096);
+    let a = arena.alloc(1u8);
+    let b = arena.alloc_array(2u8, 64);
+    let c = arena.alloc_align(3u8, 8192);
+    let d = arena.alloc(4u8);
+    assert_eq!(0, a as *const _ as usize % 4096);
+    assert_eq!(0, b.as_ptr() as usize % 4096);
+    assert_eq!(0, c as *const _ as usize % 8192);
+    assert_eq!((1, 3, 4), (*a, *c, *d));
+    assert_eq!(&[2u8; 64][..], b);
+}
+
+#[test]
+fn default_01() {
+    let arena = Arena::default();
+    let a = arena.alloc_array('A', 2000);
+    let b = arena.alloc('B');
+    assert_eq!(&['A'; 2000][..], a);
+    assert_eq!('B', *b);
+}
+
+#[test]
+fn lots_of_allocs_01() {
+    // To force multiple blocks.
+    let arena = Arena::new().with_block_size(64);
+
+    for _ in 0..512 {
+        let a = arena.alloc('A');
+        assert_eq!('A', *a);
+    }
+}
+
+#[test]
+fn big_alloc_01() {
+    // To make sure larger-than-block-size allocations succeed.
+    let arena = Arena::new().with_block_size(64);
+    let a = arena.alloc('A');
+    let b = arena.alloc('B');
+    let c = arena.alloc_array(['C'; 8], 32);
+    let d = arena.alloc('D');
+    let e = arena.alloc('E');
+
+    assert_eq!('A', *a);
+    assert_eq!('B', *b);
+    assert_eq!(&[['C'; 8]; 32], c);
+    assert_eq!('D', *d);
+    assert_eq!('E', *e);
+}
+
+#[test]
+fn stack_box_01() {
+    let log = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let arena = Arena::new();
+    let first = arena.alloc(0u64) as *mut u64;
+    let a = arena.push(DropRecorder(1, log.clone()));
+    let b = arena.push(5u64);
+    assert_eq!(5, StackBox::pop(b));
+    drop(a);
+    assert_eq!(vec![1], *log.lock().unwrap());
+    assert_eq!(unsafe { first.add(1) }, arena.alloc(1u64) as *mut u64);
+
+    // Out of order, only the last one is reclaimed.
+    let c = arena.push(1u64);
+    let d = arena.push(2u64);
+    let d_ptr = &*d as *const u64;
+    drop(c);
+    drop(d);
+    assert_eq!(d_ptr, arena.alloc(3u64) as *const u64);
+}
+
+#[test]
+fn pop_last_01() {
+    let arena = Arena::new();
+    let a = arena.alloc_array(1u32, 4);
+    let a_ptr = a.as_ptr();
+    let b = arena.alloc(2u32);
+    unsafe {
+        assert!(!arena.pop_last(a));
+        assert!(arena.pop_last(b));
+    }
+    let c = arena.copy_slice(&[5u32, 6]);
+    assert_eq!(unsafe { a_ptr.add(4) }, c.as_ptr());
+}
+
+#[test]
+fn handle_01() {
+    let mut arena = Arena::new();
+    let a = arena.alloc_handle(1u32);
+    let b = arena.alloc_handle([2u8; 3]);
+    *arena.get_mut(a).unwrap() += 10;
+    assert_eq!(Some(&11), arena.get(a));
+    assert_eq!(Some(&[2, 2, 2]), arena.get(b));
+
+    arena.clear();
+    let c = arena.alloc_handle(3u32);
+    assert_eq!(a.index(), c.index());
+    assert_eq!(None, arena.get(a));
+    assert_eq!(None, arena.get(b));
+    assert_eq!(Some(&3), arena.get(c));
+}
+
+#[test]
+fn handle_02() {
+    let mut arena = Arena::new();
+    let a = arena.alloc_handle(1u64);
+    let b = arena.temp().alloc_handle(2u64);
+    let c = arena.alloc_handle(3u64);
+    assert_eq!(b.index(), c.index());
+    assert_eq!(Some(&1), arena.get(a));
+    assert_eq!(None, arena.get(b));
+    assert_eq!(Some(&3), arena.get(c));
+
+    // Handles from another arena can't be used to reinterpret values.
+    let other = Arena::new();
+    let d = other.alloc_handle(1.0f64);
+    assert_eq!(None, arena.get(d));
+}
+
+#[test]
+fn offset_ptr_01() {
+    let arena = Arena::new();
+    let ptrs = arena.alloc_array(ArenaOffsetPtr::<u32>::null(), 2);
+    let values = arena.copy_slice(&[1u32, 2]);
+    assert!(ptrs[0].is_null());
+    assert_eq!(None, unsafe { ptrs[0].as_ref() });
+
+    ptrs[0].set_ptr(unsafe { values.as_mut_ptr().add(1) });
+    ptrs[1].set(&values[0]);
+    assert_eq!(&values[1] as *const u32, ptrs[0].as_ptr());
+    assert_eq!(Some(&1), unsafe { ptrs[1].as_ref() });
+    ptrs[1].set_ptr(std::ptr::null());
+    assert!(ptrs[1].is_null());
+
+    *unsafe { ptrs[0].as_mut() }.unwrap() = 5;
+    assert_eq!(&[1, 5], values);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "can't point at its own address")]
+fn offset_ptr_02() {
+    #[repr(C)]
+    struct Node {
+        next: ArenaOffsetPtr<Node>,
+        value: u32,
+    }
+
+    let mut node = Node {
+        next: ArenaOffsetPtr::null(),
+        value: 1,
+    };
+    let node_ptr: *const Node = &node;
+    node.next.set(unsafe { &*node_ptr });
+    assert_eq!(1, node.value);
+}
+
+#[test]
+fn reset_01() {
+    let log = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut arena = Arena::new().with_block_size(64);
+    arena.alloc_droppable(DropRecorder(1, log.clone()));
+    let big = arena.alloc_array(0u8, 1000).as_ptr();
+    let small = arena.alloc_array(0u8, 40).as_ptr();
+    let handle = arena.alloc_handle(1u32);
+    arena.region("a").alloc(1u32);
+
+    arena.reset();
+    assert_eq!(vec![1], *log.lock().unwrap());
+    assert_eq!(None, arena.get(handle));
+    assert!(arena.region_mut("a").is_some());
+
+    // The blocks are reused, with the small allocation not taking up the
+    // big block.
+    assert_eq!(small, arena.alloc_array(1u8, 40).as_ptr());
+    assert_eq!(big, arena.alloc_array(1u8, 1000).as_ptr());
+}
+
+#[test]
+fn retention_policy_01() {
+    use kioku::RetentionPolicy;
+
+    let mut arena = Arena::new()
+        .with_block_size(64)
+        .with_retention_policy(RetentionPolicy::LargestBlock);
+    arena.alloc_array(0u8, 60);
+    let big = arena.alloc_array(0u8, 1000).as_ptr();
+    arena.clear();
+    assert_eq!(big, arena.alloc_array(0u8, 10).as_ptr());
+
+    arena.clear_with(RetentionPolicy::UpToBytes(500));
+    assert_ne!(big, arena.alloc_array(0u8, 10).as_ptr());
+
+    arena.alloc_array(0u8, 1000);
+    arena.clear_with(RetentionPolicy::Nothing);
+    arena.alloc_array(0u8, 10);
+}
+
+#[test]
+fn reserve_01() {
+    let arena = Arena::new().with_block_size(64);
+    arena.reserve(10);
+    let a = arena.alloc_array(0u8, 10).as_ptr();
+    arena.reserve(10);
+    let b = arena.alloc_array(0u8, 10).as_ptr();
+    assert_eq!(unsafe { a.add(10) }, b);
+
+    arena.reserve(2000);
+    let c = arena.alloc_array(0u8, 1000).as_ptr();
+    let d = arena.alloc_array(0u8, 1000).as_ptr();
+    assert_ne!(unsafe { b.add(10) }, c);
+    assert_eq!(unsafe { c.add(1000) }, d);
+}
+
+#[test]
+fn with_capacity_01() {
+    let mut arena = Arena::new().with_block_size(64).with_capacity(1000);
+    let a = arena.alloc_array(0u8, 600).as_ptr();
+    let b = arena.alloc_array(0u8, 400).as_ptr();
+    assert_eq!(unsafe { a.add(600) }, b);
+
+    arena.reset();
+    assert_eq!(1000, arena.trim());
+}
+
+#[test]
+fn shrink_to_fit_01() {
+    let mut arena = Arena::new().with_block_size(1024);
+    let a = arena.alloc_array(0u8, 100).as_ptr();
+    arena.shrink_to_fit();
+    let b = arena.alloc_array(0u8, 100).as_ptr();
+    assert_ne!(unsafe { a.add(100) }, b);
+
+    // The block gets its full capacity back when reused.
+    arena.reset();
+    let c = arena.alloc_array(0u8, 1000).as_ptr();
+    let d = arena.alloc_array(0u8, 24).as_ptr();
+    assert_eq!(unsafe { c.add(1000) }, d);
+}
+
+#[test]
+fn trim_01() {
+    let mut arena = Arena::new().with_block_size(64);
+    arena.alloc_array(0u8, 1000);
+    arena.alloc_array(0u8, 40);
+    arena.reset();
+    let a = arena.alloc(1u32) as *mut u32;
+    assert_eq!(1000, arena.trim());
+    assert_eq!(0, arena.trim());
+
+    // Live allocations are kept.
+    let b = arena.alloc(2u32) as *mut u32;
+    assert_eq!(unsafe { a.add(1) }, b);
+    assert_eq!(1, unsafe { *a });
+}
+
+#[test]
+fn trim_02() {
+    // The current block is kept even if it's empty.
+    let mut arena = Arena::new().with_block_size(64);
+    let a = arena.alloc(1u32);
+    arena.alloc_array(0u8, 1000);
+    assert!(unsafe { arena.pop_last(a) });
+    assert_eq!(0, arena.trim());
+    assert_eq!(2, arena.stats().block_count);
+    assert_eq!(1, arena.stats().oversize_block_count);
+}
+
+#[test]
+fn stats_01() {
+    let mut arena = Arena::new().with_block_size(1024);
+    assert_eq!(ArenaStats::default(), arena.stats());
+
+    arena.alloc_array(0u8, 100);
+    arena.alloc_array(0u8, 2000);
+    let stats = arena.stats();
+    assert_eq!(3024, stats.occupied);
+    assert_eq!(2100, stats.allocated);
+    assert_eq!(2, stats.block_count);
+    assert_eq!(1, stats.oversize_block_count);
+    assert_eq!(2000, stats.oversize_allocated);
+    assert_eq!(
+        "2100 bytes allocated in 2 allocations, 3024 bytes occupied, 2 blocks (1 oversize, with 2000 bytes)",
+        stats.to_string()
+    );
+
+    arena.clear();
+    assert_eq!(
+        ArenaStats {
+            total_alloc_count: 2,
+            peak_allocated: 2100,
+            peak_block_count: 2,
+            ..ArenaStats::default()
+        },
+        arena.stats()
+    );
+}
+
+#[test]
+fn stats_02() {
+    let mut arena = Arena::new();
+    arena.alloc(1u32);
+    arena.copy_str("Hello");
+    arena.alloc_array(0u8, 0);
+    assert_eq!(3, arena.stats().alloc_count);
+
+    arena.clear();
+    arena.alloc(2u64);
+    assert_eq!(1, arena.stats().alloc_count);
+    assert_eq!(4, arena.stats().total_alloc_count);
+}
+
+#[test]
+fn stats_03() {
+    let mut arena = Arena::new().with_block_size(64);
+    arena.alloc_array(0u8, 50);
+    arena.alloc_array(0u8, 50);
+    arena.alloc_array(0u8, 50);
+    arena.clear();
+    arena.alloc_array(0u8, 10);
+
+    let stats = arena.stats();
+    assert_eq!(10, stats.allocated);
+    assert_eq!(150, stats.peak_allocated);
+    assert_eq!(1, stats.block_count);
+    assert_eq!(3, stats.peak_block_count);
+}
+
+#[test]
+fn stats_04() {
+    let arena = Arena::new().with_block_size(128);
+    arena.alloc_array(0u8, 64);
+    // Fits in a shared block, but starting a new one would waste too much of
+    // the current one.
+    arena.alloc_array(0u8, 80);
+    arena.alloc_array(0u8, 32);
+
+    let stats = arena.stats();
+    assert_eq!(2, stats.block_count);
+    assert_eq!(1, stats.oversize_block_count);
+    assert_eq!(80, stats.oversize_allocated);
+}
+
+#[test]
+fn allocated_chunks_01() {
+    let mut arena = Arena::new().with_block_size(64);
+    assert_eq!(0, arena.allocated_chunks().count());
+
+    arena.alloc_array(1u8, 40);
+    arena.alloc_array(2u8, 40);
+    arena.alloc_array(3u8, 200);
+
+    let mut chunks: Vec<Vec<u8>> = arena
+        .allocated_chunks()
+        .map(|chunk| chunk.iter().map(|b| unsafe { b.assume_init() }).collect())
+        .collect();
+    chunks.sort();
+    assert_eq!(vec![vec![1u8; 40], vec![2u8; 40], vec![3u8; 200]], chunks);
+}
+
+#[test]
+fn owns_01() {
+    let mut arena = Arena::new().with_block_size(64);
+    let other = Arena::new();
+    let a = arena.alloc(1u32) as *const u32;
+    let b = arena.alloc_array(0u8, 1000) as *const [u8];
+    let c = other.alloc(2u32) as *const u32;
+    assert!(arena.owns(a));
+    assert!(arena.owns(b));
+    assert!(!arena.owns(c));
+    assert!(!arena.owns(&0u32));
+
+    arena.clear();
+    assert!(!arena.owns(a));
+}
+
+#[test]
+fn will_fit_01() {
+    use std::alloc::Layout;
+
+    let arena = Arena::new().with_block_size(64);
+    assert!(!arena.will_fit(Layout::new::<u8>()));
+
+    arena.alloc(0u8);
+    assert!(arena.will_fit(Layout::new::<[u8; 63]>()));
+    assert!(!arena.will_fit(Layout::new::<[u8; 64]>()));
+    // Alignment padding is accounted for.
+    assert!(arena.will_fit(Layout::new::<[u64; 7]>()));
+    assert!(!arena.will_fit(Layout::new::<[u64; 8]>()));
+    assert!(!arena.will_fit(Layout::from_size_align(56, 64).unwrap()));
+}
+
+#[test]
+fn report_01() {
+    let arena = Arena::new().with_block_size(1024);
+    arena.alloc_array(0u8, 100);
+    arena.alloc_array(0u8, 2000);
+    assert_eq!(
+        "Allocated:   2100 bytes in 2 allocations\n\
+         Occupied:    3024 bytes (30.6% waste)\n\
+         Blocks:      1024, 2000\n\
+         Oversize:    1 block, with 2000 bytes\n\
+         Peak:        2100 bytes allocated, 2 blocks\n",
+        arena.report()
+    );
+
+    let arena = Arena::new().with_block_size(1024);
+    arena.alloc(0u8);
+    assert_eq!(
+        "Allocated:   1 byte in 1 allocation\n\
+         Occupied:    1024 bytes (99.9% waste)\n\
+         Blocks:      1024\n\
+         Oversize:    0 blocks, with 0 bytes\n\
+         Peak:        1 byte allocated, 1 block\n",
+        arena.report()
+    );
+}
+
+#[cfg(feature = "instrument")]
+#[test]
+fn alloc_histogram_01() {
+    let mut arena = Arena::new();
+    arena.alloc(0u8);
+    arena.alloc_array(0u8, 9);
+    arena.alloc_array(0u16, 8);
+    arena.alloc_array(0u128, 0);
+    arena.clear();
+    arena.alloc_array_align(0u8, 100, 64);
+
+    let histogram = arena.alloc_histogram();
+    assert_eq!(
+        vec![(1, 2), (16, 2), (128, 1)],
+        histogram.sizes().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![(1, 2), (2, 1), (16, 1), (64, 1)],
+        histogram.alignments().collect::<Vec<_>>()
+    );
+}
+
+#[cfg(feature = "instrument")]
+#[test]
+fn call_sites_01() {
+    let mut arena = Arena::new();
+    let line = line!();
+    arena.alloc_array(0u8, 10);
+    arena.copy_str("Hello");
+    for _ in 0..2 {
+        arena.alloc_array_with(4, |i| i as u32);
+    }
+    arena.clear();
+    arena.alloc_array(0u8, 10);
+
+    let call_sites = arena.call_sites();
+    assert_eq!(4, call_sites.len());
+    assert!(call_sites.iter().all(|c| c.location.file() == file!()));
+    let summary: Vec<_> = call_sites
+        .iter()
+        .map(|c| (c.location.line() - line, c.alloc_count, c.allocated))
+        .collect();
+    assert_eq!(vec![(4, 2, 32), (1, 1, 10), (7, 1, 10), (2, 1, 5)], summary);
+}
+
+#[test]
+fn with_hooks_01() {
+    use kioku::RetentionPolicy;
+    use std::alloc::Layout;
+    use std::sync::{Arc, Mutex};
+
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+    impl ArenaHooks for Recorder {
+        fn on_alloc(&self, layout: Layout) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("alloc {} {}", layout.size(), layout.align()));
+        }
+        fn on_new_block(&self, size: usize, oversize: bool) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("block {} {}", size, oversize));
+        }
+    }
+
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut arena = Arena::new()
+        .with_block_size(64)
+        .with_retention_policy(RetentionPolicy::Everything)
+        .with_hooks(Recorder(log.clone()));
+    arena.alloc(1u32);
+    arena.alloc_array(0u8, 100);
+    arena.clear();
+    arena.alloc(2u64);
+
+    assert_eq!(
+        vec![
+            "alloc 4 4",
+            "block 64 false",
+            "alloc 100 1",
+            "block 100 true",
+            "alloc 8 8",
+        ],
+        *log.lock().unwrap()
+    );
+}
+
+#[test]
+#[should_panic(expected = "must not use the arena")]
+fn with_hooks_02() {
+    use std::alloc::Layout;
+    use std::sync::atomic::{AtomicPtr, Ordering};
+    use std::sync::Arc;
+
+    // A hook that allocates from its own arena.
+    struct Reentrant(Arc<AtomicPtr<Arena>>);
+    impl ArenaHooks for Reentrant {
+        fn on_alloc(&self, _layout: Layout) {
+            let arena = unsafe { &*self.0.load(Ordering::Relaxed) };
+            arena.alloc(1u8);
+        }
+    }
+
+    let ptr = Arc::new(AtomicPtr::new(std::ptr::null_mut()));
+    let mut arena = Arena::new().with_hooks(Reentrant(ptr.clone()));
+    ptr.store(&mut arena, Ordering::Relaxed);
+    arena.alloc(1u32);
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn tracing_01() {
+    use std::sync::{Arc, Mutex};
+    use tracing::{span, Event, Metadata, Subscriber};
+
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+        fn event(&self, event: &Event<'_>) {
+            let names: Vec<_> = event.fields().map(|f| f.name()).collect();
+            self.0.lock().unwrap().push(names.join(" "));
+        }
+        fn enter(&self, _: &span::Id) {}
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    let log = Arc::new(Mutex::new(Vec::new()));
+    tracing::subscriber::with_default(Recorder(log.clone()), || {
+        let arena = Arena::new().with_block_size(64);
+        arena.alloc(1u32);
+        arena.alloc_array(0u8, 100);
+    });
+
+    assert_eq!(
+        vec![
+            "message size oversize",
+            "message size oversize",
+            "message size align",
+        ],
+        *log.lock().unwrap()
+    );
+}
+
+#[test]
+fn arena_metrics_01() {
+    use kioku::ArenaMetrics;
+
+    let arena = SyncArena::new();
+    arena.alloc_array(0u8, 10);
+    let stats = arena.arena_stats();
+    assert_eq!(arena.stats(), stats);
+    assert_eq!(10, stats.allocated);
+
+    let counters = stats.counters();
+    assert_eq!(("allocated_bytes", 10), counters[1]);
+    assert_eq!(("allocations", 1), counters[5]);
+}
+
+#[test]
+fn reset_stats_01() {
+    let mut arena = Arena::new().with_block_size(64);
+    arena.alloc_array(0u8, 50);
+    arena.alloc_array(0u8, 50);
+    arena.clear();
+    arena.alloc_array(0u8, 10);
+    arena.reset_stats();
+
+    let stats = arena.stats();
+    assert_eq!(0, stats.alloc_count);
+    assert_eq!(0, stats.total_alloc_count);
+    assert_eq!(10, stats.peak_allocated);
+    assert_eq!(1, stats.peak_block_count);
+    assert_eq!(10, stats.allocated);
+    assert_eq!(64, stats.occupied);
+}
+
+#[test]
+fn try_alloc_01() {
+    let arena = Arena::new();
+    assert_eq!(Ok(&mut 42u32), arena.try_alloc(42u32));
+    assert_eq!(&[7u16; 3], arena.try_alloc_array(7u16, 3).unwrap());
+    assert_eq!(&[1, 2, 3], arena.try_copy_slice(&[1, 2, 3]).unwrap());
+    assert_eq!("Hello", arena.try_copy_str("Hello").unwrap());
+    assert_eq!(5, arena.try_alloc_array_uninit::<u64>(5).unwrap().len());
+}
+
+#[test]
+fn try_alloc_02() {
+    let arena = Arena::new();
+    arena.alloc(1u32);
+    let stats = arena.stats();
+
+    // Too big to allocate, or even to represent.
+    assert_eq!(
+        Err(AllocError),
+        arena.try_alloc_array(0u8, isize::MAX as usize - 4096)
+    );
+    assert!(arena.try_alloc_array_uninit::<u64>(usize::MAX / 4).is_err());
+
+    // The arena is unaffected.
+    assert_eq!(stats.allocated, arena.stats().allocated);
+    assert_eq!(stats.block_count, arena.stats().block_count);
+    assert_eq!(&2, arena.alloc(2u32));
+}
+
+#[test]
+fn try_alloc_raw_01() {
+    use kioku::ArenaAllocError;
+    use std::alloc::Layout;
+
+    let arena = Arena::new();
+    let a = arena.try_alloc_raw(Layout::new::<u32>()).unwrap();
+    let b = arena.try_alloc_raw(Layout::new::<u32>()).unwrap();
+    assert_eq!(unsafe { a.as_ptr().add(4) }, b.as_ptr());
+
+    let huge = Layout::from_size_align(isize::MAX as usize / 2, 8).unwrap();
+    assert!(matches!(
+        arena.try_alloc_raw(huge),
+        Err(ArenaAllocError::OutOfMemory(layout)) if layout.size() == huge.size()
+    ));
+
+    // Valid on its own, but not when padded out to the block alignment.
+    let too_big = Layout::from_size_align(isize::MAX as usize - 7, 8).unwrap();
+    assert_eq!(
+        Err(ArenaAllocError::LayoutOverflow),
+        arena.try_alloc_raw(too_big)
+    );
+}
+
+#[test]
+fn with_memory_limit_01() {
+    use kioku::ArenaAllocError;
+    use std::alloc::Layout;
+
+    let mut arena = Arena::new().with_block_size(1024).with_memory_limit(2048);
+    arena.alloc_array(0u8, 1000);
+    arena.alloc_array(0u8, 1000);
+    assert_eq!(
+        Err(ArenaAllocError::MemoryLimitExceeded),
+        arena.try_alloc_raw(Layout::new::<[u8; 100]>())
+    );
+    assert_eq!(2048, arena.stats().occupied);
+
+    // Freed memory no longer counts.
+    arena.clear();
+    arena.alloc_array(0u8, 1000);
+    arena.alloc_array(0u8, 1000);
+}
+
+#[test]
+#[should_panic(expected = "Arena memory limit exceeded")]
+fn with_memory_limit_02() {
+    let arena = Arena::new().with_block_size(1024).with_memory_limit(2048);
+    arena.alloc_array(0u8, 1000);
+    arena.alloc_array(0u8, 2000);
+}
+
+#[test]
+fn with_memory_limit_03() {
+    // Memory that's still allocated but not in use counts.
+    let mut arena = Arena::new().with_block_size(1024).with_memory_limit(3000);
+    arena.alloc_array(0u8, 1000);
+    arena.alloc_array(0u8, 1500);
+    arena.reset();
+    arena.alloc_array(0u8, 1000);
+    assert!(arena.try_alloc_array(0u8, 2000).is_err());
+
+    // Unless freeing it makes enough room.
+    assert!(arena.try_alloc_array(0u8, 1800).is_ok());
+    assert_eq!(2824, arena.stats().occupied);
+
+    let arena = Arena::new().with_block_size(1024).with_memory_limit(1500);
+    arena.alloc_array(0u8, 10);
+    arena.shrink_to_fit();
+    assert!(arena.try_alloc_array(0u8, 10).is_err());
+}
+
+#[test]
+#[should_panic(expected = "Arena failed to allocate a block")]
+fn with_oom_policy_01() {
+    use kioku::OomPolicy;
+
+    let arena = Arena::new().with_oom_policy(OomPolicy::Panic);
+    arena.alloc_array(0u8, isize::MAX as usize / 2);
+}
+
+#[test]
+#[should_panic(expected = "Custom handler")]
+fn with_oom_policy_02() {
+    use kioku::OomPolicy;
+    use std::alloc::Layout;
+
+    fn handler(layout: Layout) -> ! {
+        panic!("Custom handler: {} bytes", layout.size())
+    }
+
+    let arena = Arena::new().with_oom_policy(OomPolicy::Handler(handler));
+    arena.alloc_array(0u8, 123);
+    let _ = arena
+        .try_alloc_array(0u8, isize::MAX as usize / 2)
+        .unwrap_err();
+    arena.alloc_raw(Layout::from_size_align(isize::MAX as usize / 2, 1).unwrap());
+}
+
+#[test]
+fn growth_strategy_01() {
+    use kioku::GrowthStrategy;
+
+    // Percentages over 100 grow quickly, but mustn't overflow.
+    let arena = Arena::new()
+        .with_block_size(64)
+        .with_growth_strategy(GrowthStrategy::Percentage(255));
+    for i in 0..20 {
+        arena.alloc_array(i as u8, 60);
+    }
+    let stats = arena.stats();
+    assert_eq!(20, stats.alloc_count);
+    assert_eq!(1200, stats.allocated);
+    assert!(stats.block_count < 20);
+}
+
+#[test]
+fn with_allocator_01() {
+    use allocator_api2::alloc::{AllocError, Allocator, Global};
+    use std::{
+        alloc::Layout,
+        ptr::NonNull,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    struct Pool {
+        live: AtomicUsize,
+    }
+
+    unsafe impl Allocator for &Pool {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            self.live.fetch_add(layout.size(), Ordering::Relaxed);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.live.fetch_sub(layout.size(), Ordering::Relaxed);
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    static POOL: Pool = Pool {
+        live: AtomicUsize::new(0),
+    };
+
+    {
+        let arena = Arena::new().with_block_size(64).with_allocator(&POOL);
+        arena.alloc_array(0u8, 32);
+        arena.alloc_array(0u8, 1000);
+        assert_eq!(1064, POOL.live.load(Ordering::Relaxed));
+
+        let region = arena.region("test");
+        region.alloc(1u8);
+        assert_eq!(1128, POOL.live.load(Ordering::Relaxed));
+    }
+    assert_eq!(0, POOL.live.load(Ordering::Relaxed));
+}
+
+#[test]
+fn with_allocator_02() {
+    use allocator_api2::alloc::{AllocError, Allocator, Global};
+    use std::{alloc::Layout, cell::Cell, ptr::NonNull};
+
+    // Neither `Sync` nor `'static`.
+    struct LocalPool {
+        live: Cell<usize>,
+    }
+
+    unsafe impl Allocator for &LocalPool {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            self.live.set(self.live.get() + layout.size());
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.live.set(self.live.get() - layout.size());
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    let pool = LocalPool { live: Cell::new(0) };
+
+    {
+        let mut arena = Arena::new_in(&pool).with_block_size(64);
+        arena.alloc_array(0u8, 32);
+        assert_eq!(64, pool.live.get());
+
+        {
+            let sub = arena.sub_arena();
+            sub.alloc_array(0u8, 100);
+            assert_eq!(164, pool.live.get());
+        }
+
+        let region = arena.region("test");
+        region.alloc(1u8);
+        assert_eq!(228, pool.live.get());
+
+        arena.clear();
+        assert!(pool.live.get() <= 64);
+    }
+    assert_eq!(0, pool.live.get());
+}
+
+#[test]
+fn with_block_source_01() {
+    use kioku::{ArenaAllocError, BlockSource, HeapSource};
+    use std::{
+        alloc::Layout,
+        ptr::NonNull,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    // Hands out a bit more memory than asked for.
+    struct Generous {
+        live: AtomicUsize,
+    }
+
+    unsafe impl BlockSource for Generous {
+        fn acquire(&self, layout: Layout) -> Result<NonNull<[u8]>, ArenaAllocError> {
+            let layout = Layout::from_size_align(layout.size() + 16, layout.align()).unwrap();
+            self.live.fetch_add(layout.size(), Ordering::Relaxed);
+            HeapSource.acquire(layout)
+        }
+
+        unsafe fn release(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.live.fetch_sub(layout.size(), Ordering::Relaxed);
+            HeapSource.release(ptr, layout)
+        }
+    }
+
+    static SOURCE: Generous = Generous {
+        live: AtomicUsize::new(0),
+    };
+
+    {
+        let mut arena = Arena::new().with_block_size(64).with_block_source(&SOURCE);
+        // The extra memory is used too.
+        arena.alloc_array(0u8, 80);
+        assert_eq!(1, arena.stats().block_count);
+        assert_eq!(80, SOURCE.live.load(Ordering::Relaxed));
+        assert_eq!(80, arena.stats().occupied);
+
+        arena.alloc_array(0u8, 100);
+        assert_eq!(196, SOURCE.live.load(Ordering::Relaxed));
+        arena.clear();
+        assert_eq!(0, SOURCE.live.load(Ordering::Relaxed));
+        arena.alloc(0u8);
+        assert_eq!(80, SOURCE.live.load(Ordering::Relaxed));
+    }
+    assert_eq!(0, SOURCE.live.load(Ordering::Relaxed));
+}
+
+#[test]
+fn buffer_source_01() {
+    use kioku::BufferSource;
+    use std::mem::MaybeUninit;
+
+    let buffer = Box::leak(vec![MaybeUninit::<u8>::uninit(); 1000].into_boxed_slice());
+    let source = BufferSource::new(buffer);
+    assert_eq!(1000, source.remaining());
+
+    let arena = Arena::new().with_block_size(256).with_block_source(source);
+    let a = arena.alloc_array(1u8, 200);
+    let b = arena.alloc_array(2u8, 200);
+    let c = arena.alloc_array(3u8, 200);
+    assert!(arena.try_alloc_array(4u8, 300).is_err());
+    assert!(arena.try_alloc_array(4u8, 200).is_ok());
+    assert_eq!((&[1; 200][..], &[2; 200][..]), (&a[..], &b[..]));
+    assert_eq!(&[3; 200][..], &c[..]);
+}
+
+#[cfg(all(unix, feature = "mmap"))]
+#[test]
+fn mmap_source_01() {
+    use kioku::MmapSource;
+    use std::alloc::Layout;
+
+    let arena = Arena::new()
+        .with_block_size(100)
+        .with_block_source(MmapSource::new());
+    let a = arena.alloc_array(1u32, 10);
+    assert_eq!(&[1; 10], a);
+    assert!(arena.stats().occupied >= 4096);
+
+    // Bigger alignment than a page.
+    let layout = Layout::from_size_align(1 << 20, 1 << 20).unwrap();
+    let big = arena.alloc_raw(layout);
+    assert_eq!(0, big as usize % (1 << 20));
+    unsafe { big.add((1 << 20) - 1).write(std::mem::MaybeUninit::new(1)) };
+}
+
+#[cfg(all(unix, feature = "mmap"))]
+#[test]
+fn mmap_source_02() {
+    use kioku::{HugePages, MmapSource};
+
+    for huge_pages in [HugePages::Transparent, HugePages::Explicit] {
+        let arena = Arena::new()
+            .with_block_size(4 << 20)
+            .with_block_source(MmapSource::new().with_huge_pages(huge_pages));
+        let a = arena.alloc_array(1u8, 3 << 20);
+        let b = arena.alloc_array(2u8, 100);
+        assert!(a.iter().all(|&n| n == 1));
+        assert!(b.iter().all(|&n| n == 2));
+        assert_eq!(4 << 20, arena.stats().occupied);
+    }
+}
+
+#[cfg(all(unix, feature = "guard_pages"))]
+#[test]
+fn with_guard_pages_01() {
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+
+    let arena = Arena::new().with_guard_pages(true);
+    let small = arena.alloc(1u32);
+    let big = arena.alloc_array(2u8, 10_001);
+    let aligned = arena.alloc_array(3u64, 5_000);
+    assert_eq!(1, *small);
+    assert!(big.iter().all(|&n| n == 2));
+    assert!(aligned.iter().all(|&n| n == 3));
+
+    // The oversized allocations end right at the start of a guard page.
+    assert_eq!(0, (big.as_ptr() as usize + big.len()) % page_size);
+    assert_eq!(
+        0,
+        (aligned.as_ptr() as usize + aligned.len() * 8) % page_size
+    );
+    assert_eq!(3, arena.stats().block_count);
+}
+
+#[test]
+fn adopt_block_01() {
+    let mut arena = Arena::new().with_block_size(64);
+    arena.alloc(1u8);
+
+    let buffer: Box<[u8]> = vec![0xFF; 1000].into_boxed_slice();
+    let addr = buffer.as_ptr() as usize;
+    arena.adopt_block(buffer);
+    let stats = arena.stats();
+    assert_eq!(2, stats.block_count);
+    assert_eq!(1064, stats.occupied);
+
+    // Allocations come from the adopted block until it's full.
+    let a = arena.alloc_array(2u8, 500);
+    let b = arena.alloc_array(3u8, 500);
+    assert_eq!(addr, a.as_ptr() as usize);
+    assert_eq!(addr + 500, b.as_ptr() as usize);
+    assert_eq!(2, arena.stats().block_count);
+    arena.alloc(4u8);
+    assert_eq!(3, arena.stats().block_count);
+
+    // Empty buffers are ignored.
+    arena.adopt_block(Vec::new());
+    assert_eq!(3, arena.stats().block_count);
+
+    // The buffer is kept for reuse like any other block.
+    arena.reset();
+    let c = arena.alloc_array(5u8, 1000);
+    assert_eq!(addr, c.as_ptr() as usize);
+
+    arena.clear();
+    assert_eq!(0, arena.stats().occupied);
+}
+
+#[test]
+fn adopt_block_02() {
+    use std::alloc::Layout;
+
+    // Adopted blocks get the arena's block alignment.
+    let arena = Arena::new().with_block_alignment(256);
+    for size in [1000, 1001, 3000] {
+        arena.adopt_block(Vec::with_capacity(size));
+        let a = arena.alloc(1u8) as *mut u8;
+        assert_eq!(0, a as usize % 256);
+
+        // Including for zero-sized allocations.
+        let b = arena.alloc_raw(Layout::from_size_align(0, 512).unwrap());
+        assert_eq!(0, b as usize % 512);
+    }
+    assert_eq!(3, arena.stats().block_count);
+
+    // Buffers too small to align are freed instead, unless they happen to
+    // be aligned already.
+    arena.adopt_block(Vec::with_capacity(1));
+}
+
+#[test]
+fn buffer_arena_01() {
+    use kioku::BufferArena;
+    use std::mem::MaybeUninit;
+
+    let mut buffer = [MaybeUninit::uninit(); 1000];
+    let buffer_addr = buffer.as_ptr() as usize;
+    let mut arena = BufferArena::new(&mut buffer);
+    assert_eq!(1000, arena.remaining());
+
+    let a = arena.alloc_array(1u8, 600);
+    assert_eq!(buffer_addr, a.as_ptr() as usize);
+    assert_eq!(400, arena.remaining());
+    assert!(arena.try_alloc_array(2u8, 500).is_err());
+    assert!(arena.try_alloc(0u8).is_ok());
+    assert_eq!(1, arena.stats().block_count);
+    assert_eq!(1000, arena.stats().occupied);
+
+    // Clearing makes the whole buffer available again, even after shrinking.
+    arena.shrink_to_fit();
+    arena.clear();
+    assert_eq!(1000, arena.remaining());
+    let b = arena.alloc_array(3u8, 1000);
+    assert_eq!(buffer_addr, b.as_ptr() as usize);
+    assert!(arena.try_alloc(0u8).is_err());
+}
+
+#[test]
+#[should_panic]
+fn buffer_arena_02() {
+    use kioku::BufferArena;
+    use std::mem::MaybeUninit;
+
+    let mut buffer = [MaybeUninit::uninit(); 100];
+    let arena = BufferArena::new(&mut buffer);
+    arena.alloc_array(0u8, 101);
+}
+
+#[test]
+fn buffer_arena_03() {
+    use kioku::{BufferArena, StackArena};
+
+    // Empty buffers can't be allocated from, but otherwise work.
+    let mut arena = BufferArena::new(&mut []);
+    assert_eq!(0, arena.remaining());
+    assert!(arena.try_alloc(1u8).is_err());
+    assert_eq!(0, arena.stats().block_count);
+    arena.clear();
+    assert!(arena.report().contains("0 bytes (0.0% waste)"));
+
+    let mut storage = StackArena::<0>::new();
+    let arena = storage.arena();
+    assert!(arena.try_alloc_array(0u32, 1).is_err());
+}
+
+#[test]
+fn stack_arena_01() {
+    use kioku::StackArena;
+
+    let mut storage = StackArena::<256>::new();
+    assert_eq!(256, storage.capacity());
+    let storage_addr = &storage as *const _ as usize;
+    assert_eq!(0, storage_addr % 16);
+
+    {
+        let arena = storage.arena();
+        let a = arena.alloc(1u128);
+        assert_eq!(storage_addr, a as *mut u128 as usize);
+        let b = arena.alloc_array(2u64, 30);
+        assert_eq!(60, b.iter().sum::<u64>());
+        assert!(arena.try_alloc(0u8).is_err());
+    }
+
+    // Starts over with a fresh arena.
+    let arena = storage.arena();
+    assert_eq!(256, arena.remaining());
+    let text = arena.copy_str("Hello");
+    assert_eq!("Hello", text);
+}
+
+#[test]
+fn leak_01() {
+    fn intern(arena: &'static Arena, text: &str) -> &'static str {
+        arena.copy_str(text)
+    }
+
+    let arena = Arena::new().with_block_size(16).leak();
+    let a = intern(arena, "Hello");
+    let b = intern(arena, "world!");
+    assert_eq!("Hello world!", format!("{} {}", a, b));
+}
+
+#[test]
+fn region_01() {
+    let mut arena = Arena::new();
+    let a = arena.region("a").alloc(1u32);
+    let b = arena.region("b").alloc(2u32);
+    let c = arena.alloc(3u32);
+    assert_eq!(&mut 1, a);
+    assert_eq!(&mut 2, b);
+    assert_eq!(&mut 3, c);
+    assert!(std::ptr::eq(arena.region("a"), arena.region("a")));
+
+    arena.clear_region("a");
+    assert!(arena.region_mut("a").is_some());
+    assert!(arena.region_mut("c").is_none());
+    arena.clear_region("c");
+
+    arena.clear();
+    assert!(arena.region_mut("a").is_none());
+}
+
+#[test]
+fn scope_01() {
+    let log = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut arena = Arena::new().with_block_size(64);
+    arena.alloc_droppable(DropRecorder(1, log.clone()));
+
+    let sum = arena.scope(|arena| {
+        arena.alloc_droppable(DropRecorder(2, log.clone()));
+        let big = arena.alloc_array(1u8, 1000);
+        let small = arena.alloc_array(2u8, 50);
+        let more = arena.alloc_array(3u8, 50);
+        big.len() + small.len() + more.len()
+    });
+    assert_eq!(1100, sum);
+    assert_eq!(vec![2], *log.lock().unwrap());
+
+    let a = arena.scope(|arena| arena.alloc(1u64) as *mut u64);
+    let b = arena.scope(|arena| arena.alloc(2u64) as *mut u64);
+    assert_eq!(a, b);
+
+    drop(arena);
+    assert_eq!(vec![2, 1], *log.lock().unwrap());
+}
+
+#[test]
+fn scope_02() {
+    let mut arena = Arena::new();
+    let a = arena.alloc(1u32) as *mut u32;
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        arena.scope(|arena| {
+            arena.alloc_array(0u32, 10000);
+            panic!();
+        })
+    }));
+    assert!(result.is_err());
+    let b = arena.alloc(2u32) as *mut u32;
+    assert_eq!(unsafe { a.add(1) }, b);
+}
+
+#[test]
+fn temp_01() {
+    let log = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut arena = Arena::new().with_block_size(64);
+    let a = arena.alloc(1u64) as *mut u64;
+    {
+        let mut temp = arena.temp();
+        temp.alloc_droppable(DropRecorder(1, log.clone()));
+        temp.alloc_array(0u8, 1000);
+        {
+            let nested = temp.temp();
+            nested.alloc_droppable(DropRecorder(2, log.clone()));
+            nested.alloc_array(0u8, 40);
+        }
+        assert_eq!(vec![2], *log.lock().unwrap());
+        temp.alloc_array(0u8, 40);
+    }
+    assert_eq!(vec![2, 1], *log.lock().unwrap());
+    let b = arena.alloc(2u64) as *mut u64;
+    assert_eq!(unsafe { a.add(1) }, b);
+}
+
+#[test]
+fn sub_arena_01() {
+    let log = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let parent = Arena::new().with_block_size(4096);
+    let a = parent.alloc(1u128) as *mut u128;
+    {
+        let mut child = parent.sub_arena().with_block_size(128);
+        child.alloc_droppable(DropRecorder(1, log.clone()));
+        let x = child.alloc_array(7u16, 100);
+        let y = child.copy_str("Hello");
+        assert!(x.iter().all(|&n| n == 7));
+        assert_eq!("Hello", y);
+        child.clear();
+        assert_eq!(vec![1], *log.lock().unwrap());
+        child.alloc_array(0u32, 40);
+    }
+    let b = parent.alloc(2u128) as *mut u128;
+    assert_eq!(unsafe { a.add(1) }, b);
+}
+
+#[test]
+fn sync_arena_01() {
+    let mut arena = SyncArena::new();
+    let all: Vec<&mut [u32]> = std::thread::scope(|s| {
+        let handles: Vec<_> = (0..4u32)
+            .map(|i| {
+                let arena = &arena;
+                s.spawn(move || {
+                    let a = arena.alloc_array(i, 1000);
+                    let b = arena.copy_slice(&a[..10]);
+                    assert_eq!(&[i; 10], b);
+                    a
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    for (i, a) in all.iter().enumerate() {
+        assert!(a.iter().all(|&n| n == i as u32));
+    }
+    arena.clear();
+    assert_eq!(&mut 5, arena.get_mut().alloc(5));
+}
+
+#[test]
+fn concurrent_arena_01() {
+    let mut arena = ConcurrentArena::new().with_block_size(256);
+    let all: Vec<Vec<&mut u64>> = std::thread::scope(|s| {
+        let handles: Vec<_> = (0..4u64)
+            .map(|i| {
+                let arena = &arena;
+                s.spawn(move || (0..1000).map(|n| arena.alloc(i * 1000 + n)).collect())
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    for (i, values) in all.iter().enumerate() {
+        for (n, v) in values.iter().enumerate() {
+            assert_eq!(i as u64 * 1000 + n as u64, **v);
+        }
+    }
+    arena.clear();
+    assert_eq!("Hello", arena.copy_str("Hello"));
+}
+
+#[test]
+fn concurrent_arena_02() {
+    let arena = ConcurrentArena::new().with_block_size(64);
+    let big = arena.alloc_array(1u8, 1000);
+    let small = arena.copy_slice(&[1u32, 2, 3]);
+    let aligned = arena.alloc_raw(std::alloc::Layout::from_size_align(8, 64).unwrap());
+    assert_eq!(1000, big.len());
+    assert_eq!(&[1, 2, 3], small);
+    assert_eq!(0, aligned as usize % 64);
+}
+
+#[test]
+#[should_panic(expected = "Array length overflow")]
+fn concurrent_arena_03() {
+    ConcurrentArena::new().alloc_array_uninit::<u64>(usize::MAX / 4);
+}
+
+#[test]
+fn sharded_arena_01() {
+    let mut arena = ShardedArena::new().with_block_size(128);
+    let all: Vec<&mut [u32]> = std::thread::scope(|s| {
+        let handles: Vec<_> = (0..4u32)
+            .map(|i| {
+                let arena = &arena;
+                s.spawn(move || {
+                    let local = arena.local();
+                    assert!(std::ptr::eq(local, arena.local()));
+                    local.alloc_array(i, 100)
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    for (i, a) in all.iter().enumerate() {
+        assert!(a.iter().all(|&n| n == i as u32));
+    }
+    assert_eq!(4, arena.shard_count());
+
+    arena.clear();
+    assert_eq!(0, arena.shard_count());
+    assert_eq!(&mut 5, arena.local().alloc(5));
+    assert_eq!(1, arena.shard_count());
+}
+
+#[test]
+fn with_scratch_01() {
+    let outer = kioku::thread_local_arena();
+    let a = outer.alloc(5u32);
+    let len = kioku::with_scratch(|arena| arena.alloc_array(1u8, 100).len());
+    assert_eq!(100, len);
+    assert_eq!(5, *a);
+    drop(outer);
+
+    let log = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    kioku::with_scratch(|arena| {
+        arena.alloc_droppable(DropRecorder(1, log.clone()));
+    });
+    assert_eq!(vec![1], *log.lock().unwrap());
+}
+
+//-----------------------------------------------------------
+// Collections.
+
+#[test]
+fn vec_01() {
+    use kioku::collections::Vec;
+
+    let arena = Arena::new();
+    let mut a = Vec::new_in(&arena);
+    for i in 0..100u32 {
+        a.push(i);
+    }
+    assert_eq!(100, a.len());
+    assert_eq!(Some(99), a.pop());
+    a.truncate(3);
+    a.insert(1, 42);
+    assert_eq!(0, a.remove(0));
+    a.extend_from_slice(&[7, 8]);
+    a.extend(&[9]);
+    assert_eq!(&[42, 1, 2, 7, 8, 9], a.into_slice());
+}
+
+#[test]
+fn vec_02() {
+    use kioku::collections::Vec;
+
+    let log = std::sync::Arc::new(std::sync::Mutex::new(std::vec::Vec::new()));
+    let arena = Arena::new();
+    let mut a = Vec::with_capacity_in(4, &arena);
+    let _b = arena.alloc(0u8); // Force `a` to move when it grows.
+    for i in 0..5 {
+        a.push(DropRecorder(i, log.clone()));
+    }
+    a.truncate(3);
+    assert_eq!(vec![3, 4], *log.lock().unwrap());
+    drop(a);
+    assert_eq!(vec![3, 4, 0, 1, 2], *log.lock().unwrap());
+}
+
+#[test]
+fn vec_deque_01() {
+    use kioku::collections::VecDeque;
+
+    let arena = Arena::new();
+    let mut a = VecDeque::new_in(&arena);
+    let mut b = std::collections::VecDeque::new();
+    for i in 0..100u32 {
+        match i % 5 {
+            0 | 1 => {
+                a.push_back(i);
+                b.push_back(i);
+            }
+            2 | 3 => {
+                a.push_front(i);
+                b.push_front(i);
+            }
+            _ => assert_eq!(b.pop_front(), a.pop_front()),
+        }
+        assert!(a.iter().eq(b.iter()));
+        assert_eq!(b.back(), a.back());
+    }
+}
+
+#[test]
+fn vec_deque_02() {
+    use kioku::collections::VecDeque;
+
+    let log = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let arena = Arena::new();
+    let mut a = VecDeque::with_capacity_in(3, &arena);
+    let _b = arena.alloc(0u8); // Force `a` to move when it grows.
+    for i in 0..3 {
+        assert!(a.try_push_front(DropRecorder(i, log.clone())).is_ok());
+    }
+    assert!(a.try_push_back(DropRecorder(3, log.clone())).is_err());
+    a.push_back(DropRecorder(4, log.clone()));
+    assert_eq!(vec![3], *log.lock().unwrap());
+    assert_eq!(2, a[0].0);
+    drop(a);
+    assert_eq!(vec![3, 2, 1, 0, 4], *log.lock().unwrap());
+}
+
+#[test]
+fn collect_in_01() {
+    use kioku::collections::{CollectIn, String, Vec, VecDeque};
+
+    let arena = Arena::new();
+    let a: Vec<u32> = (0..5).collect_in(&arena);
+    let b: VecDeque<u32> = (0..5).collect_in(&arena);
+    let c: String = ["ab", "cd"].iter().copied().collect_in(&arena);
+    let d: String = "xyz".chars().collect_in(&arena);
+    assert_eq!(&[0, 1, 2, 3, 4], a.as_slice());
+    assert!(b.iter().copied().eq(0..5));
+    assert_eq!("abcd", c.as_str());
+    assert_eq!("xyz", d.as_str());
+    assert_eq!("zyx", "xyz".chars().rev().collect_str_in(&arena));
+}
+
+#[test]
+fn string_01() {
+    use kioku::collections::String;
+    use std::fmt::Write;
+
+    let arena = Arena::new().with_block_size(64);
+    let mut a = String::new_in(&arena);
+    for i in 0..100 {
+        write!(a, "{} ", i).unwrap();
+    }
+    a.push('学');
+    let b = String::new_in(&arena);
+
+    let expected: std::string::String = (0..100).map(|i| format!("{} ", i)).collect();
+    assert_eq!(expected + "学", &*a.into_str());
+    assert_eq!("", b.into_str());
+}
+
+#[test]
+fn string_02() {
+    use kioku::collections::String;
+
+    // Interleaved with other allocations, so it can't grow in place.
+    let arena = Arena::new();
+    let mut a = String::from_str_in("Hello", &arena);
+    let b = arena.alloc('B');
+    a.push_str(" there! こんにちは！");
+    let c = arena.alloc('C');
+
+    assert_eq!("Hello there! こんにちは！", a.into_str());
+    assert_eq!('B', *b);
+    assert_eq!('C', *c);
+}
+
+#[test]
+fn fixed_vec_01() {
+    use kioku::collections::FixedVec;
+
+    let arena = Arena::new();
+    let mut a = FixedVec::with_capacity_in(3, &arena);
+    let mut b = FixedVec::<char>::with_capacity_in(0, &arena);
+    a.push('A');
+    a.push('B');
+    assert_eq!(Some('B'), a.pop());
+    a.push('C');
+    a.push('D');
+    assert!(a.is_full());
+    assert_eq!(Err('E'), a.try_push('E'));
+    assert_eq!(Err('F'), b.try_push('F'));
+
+    assert_eq!(&['A', 'C', 'D'], a.into_slice());
+    assert_eq!(&['G'; 0][..], b.into_slice());
+}
+
+#[test]
+#[should_panic]
+fn fixed_vec_02() {
+    use kioku::collections::FixedVec;
+
+    let arena = Arena::new();
+    let mut a = FixedVec::with_capacity_in(1, &arena);
+    a.push('A');
+    a.push('B');
+}
+
+//-----------------------------------------------------------
+// IO.
+
+#[test]
+fn arena_writer_01() {
+    use std::io::Write;
+
+    let arena = Arena::new().with_block_size(64);
+    let mut a = ArenaWriter::new(&arena);
+    for i in 0..100u8 {
+        a.write_all(&[i, i]).unwrap();
+    }
+    let b = ArenaWriter::new(&arena);
+
+    let expected: Vec<u8> = (0..100u8).flat_map(|i| vec![i, i]).collect();
+    assert_eq!(&expected[..], a.into_slice());
+    assert_eq!(&[0u8; 0][..], b.into_slice());
+}
+
+#[cfg(feature = "bstr")]
+#[test]
+fn arena_writer_bstr_01() {
+    use std::io::Write;
+
+    let arena = Arena::new();
+    let mut a = ArenaWriter::new(&arena);
+    a.write_all(b"Hello\xff").unwrap();
+    assert_eq!(bstr::BStr::new(b"Hello\xff"), a.into_bstr());
+}
+
+#[test]
+fn alloc_from_reader_01() {
+    let data: Vec<u8> = (0..5000u32).map(|n| (n % 251) as u8).collect();
+
+    let arena = Arena::new().with_block_size(64);
+    let a = arena.alloc_from_reader(&mut &data[..], 0).unwrap();
+    let b = arena.alloc_from_reader(&mut &data[..], data.len()).unwrap();
+    let c = arena.alloc_from_reader(&mut &data[..10], 1000).unwrap();
+    let d = arena.alloc_from_reader(&mut &data[..0], 0).unwrap();
+
+    assert_eq!(&data[..], a);
+    assert_eq!(&data[..], b);
+    assert_eq!(&data[..10], c);
+    assert_eq!(&[0u8; 0][..], d);
+}
+
+#[test]
+fn alloc_from_reader_02() {
+    let data = vec![7u8; 100_000];
+
+    // An exact size hint doesn't need any more room than the data itself.
+    let arena = Arena::new().with_block_size(1024);
+    let a = arena.alloc_from_reader(&mut &data[..], data.len()).unwrap();
+    assert_eq!(&data[..], a);
+    assert!(arena.stats().occupied <= data.len() + 1024);
+    assert_eq!(1, arena.stats().oversize_block_count);
+}
+
+//-----------------------------------------------------------
+// Tests to make sure malformed alignments are rejected.
+
+#[test]
+#[should_panic]
+fn alloc_align_malformed_01() {
+    Arena::new().alloc_align('A', 6);
+}
+
+#[test]
+#[should_panic]
+fn alloc_align_malformed_02() {
+    Arena::new().alloc_align('A', 0);
+}
+
+//-----------------------------------------------------------
+// Tests to make sure zero-sized types are rejected.
+
+#[test]
+#[should_panic]
+fn zero_sized_types_01() {
+    Arena::new().alloc(());
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_02() {
+    Arena::new().alloc_array((), 0);
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_03() {
+    Arena::new().copy_slice(&[()]);
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_04() {
+    Arena::new().alloc_align((), 4);
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_05() {
     Arena::new().alloc_array_align((), 0, 4);
 }
 
@@ -214,3 +2469,117 @@ fn zero_sized_types_09() {
 fn zero_sized_types_10() {
     Arena::new().alloc_array_align_uninit::<()>(0, 4);
 }
+
+#[test]
+#[should_panic]
+fn zero_sized_types_11() {
+    Arena::new().copy_slice_clone(&[()]);
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_12() {
+    Arena::new().alloc_clone(&());
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_13() {
+    unsafe { Arena::new().alloc_zeroed::<()>() };
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_14() {
+    unsafe { Arena::new().alloc_array_zeroed::<()>(0) };
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_15() {
+    Arena::new().alloc_array_strided((), 0, 4);
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_16() {
+    Arena::new().alloc_array_2d((), 0, 0, 4);
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_17() {
+    Arena::new().alloc_header_array((), 'A', 0);
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_18() {
+    Arena::new().alloc_iter_chunks(vec![(); 0]);
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_19() {
+    Arena::new().alloc_droppable(());
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_20() {
+    ABox::new_in((), &Arena::new());
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_21() {
+    TypedArena::<()>::new();
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_22() {
+    Arena::new().alloc_vec(vec![(); 3]);
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_23() {
+    Arena::new().alloc_pinned(());
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_24() {
+    Arena::new().alloc_pinned_uninit::<()>();
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_25() {
+    kioku::collections::Vec::<()>::new_in(&Arena::new());
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_26() {
+    kioku::collections::VecDeque::<()>::new_in(&Arena::new());
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_27() {
+    ConcurrentArena::new().alloc(());
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_28() {
+    Arena::new().alloc_handle(());
+}
+
+#[test]
+#[should_panic]
+fn zero_sized_types_29() {
+    Arena::new().push(());
+}